@@ -0,0 +1,602 @@
+//! On-disk data structures used by easy-fs: directory entries and the
+//! on-disk inode, addressed through direct, single-indirect, and
+//! double-indirect block pointers the same way the rest of this crate's
+//! block allocator expects.
+
+use super::{get_block_cache, BlockDevice, BLOCK_SZ};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// Number of data blocks a `DiskInode` can address directly, without going
+/// through an indirect block
+const INODE_DIRECT_COUNT: usize = 28;
+/// Number of block-id entries ("u32"s) that fit in one block, i.e. how
+/// many data blocks a single indirect block can address
+const INODE_INDIRECT1_COUNT: usize = BLOCK_SZ / 4;
+/// How many data blocks a double-indirect block can address
+const INODE_INDIRECT2_COUNT: usize = INODE_INDIRECT1_COUNT * INODE_INDIRECT1_COUNT;
+const DIRECT_BOUND: usize = INODE_DIRECT_COUNT;
+const INDIRECT1_BOUND: usize = DIRECT_BOUND + INODE_INDIRECT1_COUNT;
+
+/// A block used purely to hold `u32` block-id pointers, for the indirect
+/// addressing levels
+type IndirectBlock = [u32; BLOCK_SZ / 4];
+/// A block holding raw file content
+type DataBlock = [u8; BLOCK_SZ];
+
+/// setuid bit of a `DiskInode`'s raw on-disk mode
+pub const S_ISUID: u32 = 0o4000;
+/// setgid bit of a `DiskInode`'s raw on-disk mode
+pub const S_ISGID: u32 = 0o2000;
+/// permission bits a freshly created inode starts with
+const DEFAULT_MODE_BITS: u32 = 0o755;
+
+/// On-disk inode: an individual file or directory's metadata plus the
+/// block pointers addressing its content
+#[repr(C)]
+pub struct DiskInode {
+    /// size of the file/directory's content, in bytes
+    pub size: u32,
+    /// directly addressed data blocks
+    pub direct: [u32; INODE_DIRECT_COUNT],
+    /// singly-indirect block of data-block pointers
+    pub indirect1: u32,
+    /// doubly-indirect block of indirect blocks
+    pub indirect2: u32,
+    /// file or directory
+    type_: DiskInodeType,
+    /// number of hard links to this inode, maintained incrementally by
+    /// [`crate::vfs::Inode::vfs_link`]/`vfs_unlink`
+    pub nlink: u32,
+    /// owning user id
+    pub uid: u32,
+    /// owning group id
+    pub gid: u32,
+    /// permission bits plus the setuid/setgid bits, in the low 12 bits,
+    /// with the inode type bits above them
+    pub mode: u32,
+    /// last access time
+    pub atime: u64,
+    /// last content modification time
+    pub mtime: u64,
+    /// last metadata change time
+    pub ctime: u64,
+    /// checksum over this inode's metadata fields (`size`, `type_`, and
+    /// the direct/indirect block pointers), maintained by
+    /// [`crate::vfs::Inode::modify_disk_inode`] and checked by
+    /// [`crate::vfs::Inode::read_disk_inode`] when the
+    /// `diskinode_checksum` feature is on. 0 on an inode written before
+    /// the feature existed, which [`Self::verify_checksum`] treats as
+    /// "not yet covered" rather than corrupt.
+    #[cfg(feature = "diskinode_checksum")]
+    pub checksum: u32,
+    /// Bumped by [`Self::initialize`] every time this on-disk slot is
+    /// (re-)allocated to a file or directory. An in-memory
+    /// [`crate::vfs::Inode`] captures the generation it saw at
+    /// construction time; if the slot is freed by `vfs_unlink` and later
+    /// reused by `create`/`mkdir` for an unrelated file, the new
+    /// generation no longer matches, and a stale handle's `read_at`/
+    /// `write_at` can tell it's pointing at someone else's data instead
+    /// of silently operating on it.
+    pub generation: u32,
+}
+
+/// The kind of filesystem object a [`DiskInode`] represents
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum DiskInodeType {
+    /// regular file
+    File,
+    /// directory
+    Directory,
+    /// symbolic link, storing its target path as regular file content
+    SymLink,
+}
+
+impl DiskInode {
+    /// Initialize a freshly allocated inode as `type_`, with no content
+    /// and a single hard link (the one being created)
+    pub fn initialize(&mut self, type_: DiskInodeType) {
+        self.size = 0;
+        self.direct.iter_mut().for_each(|v| *v = 0);
+        self.indirect1 = 0;
+        self.indirect2 = 0;
+        self.type_ = type_;
+        self.nlink = 1;
+        self.uid = 0;
+        self.gid = 0;
+        let type_bits = match type_ {
+            DiskInodeType::File => 0o100000,
+            DiskInodeType::Directory => 0o040000,
+            DiskInodeType::SymLink => 0o120000,
+        };
+        self.mode = type_bits | DEFAULT_MODE_BITS;
+        self.atime = 0;
+        self.mtime = 0;
+        self.ctime = 0;
+        self.generation = self.generation.wrapping_add(1);
+        #[cfg(feature = "diskinode_checksum")]
+        {
+            self.checksum = self.compute_checksum();
+        }
+    }
+
+    /// Fold this inode's metadata fields (`size`, `type_`, and the
+    /// direct/indirect block pointers -- everything [`Self::get_block_id`]
+    /// trusts to find content) into a single `u32`. Not cryptographic,
+    /// just enough to catch a flipped bit or a stray write landing on the
+    /// wrong block.
+    #[cfg(feature = "diskinode_checksum")]
+    pub fn compute_checksum(&self) -> u32 {
+        let mut crc: u32 = 0xFFFF_FFFF;
+        let mut fold = |word: u32| {
+            crc ^= word;
+            for _ in 0..32 {
+                if crc & 1 != 0 {
+                    crc = (crc >> 1) ^ 0xEDB8_8320;
+                } else {
+                    crc >>= 1;
+                }
+            }
+        };
+        fold(self.size);
+        fold(self.type_ as u32);
+        for block in self.direct.iter() {
+            fold(*block);
+        }
+        fold(self.indirect1);
+        fold(self.indirect2);
+        !crc
+    }
+
+    /// Whether the stored [`Self::checksum`] matches the metadata that's
+    /// actually there. A freshly-zeroed inode (stored checksum `0`,
+    /// never run through [`Self::initialize`]) is treated as not-yet-
+    /// covered rather than corrupt, so images written before this
+    /// feature existed don't fail every read.
+    #[cfg(feature = "diskinode_checksum")]
+    pub fn verify_checksum(&self) -> bool {
+        self.checksum == 0 || self.checksum == self.compute_checksum()
+    }
+
+    /// Whether this inode is a directory
+    pub fn is_dir(&self) -> bool {
+        self.type_ == DiskInodeType::Directory
+    }
+    /// Whether this inode is a regular file
+    pub fn is_file(&self) -> bool {
+        self.type_ == DiskInodeType::File
+    }
+    /// Whether this inode is a symbolic link
+    pub fn is_symlink(&self) -> bool {
+        self.type_ == DiskInodeType::SymLink
+    }
+
+    /// The physical block id backing the `inner_id`-th data block of this
+    /// inode's content, following indirect block pointers as needed
+    pub fn get_block_id(&self, inner_id: u32, block_device: &Arc<dyn BlockDevice>) -> u32 {
+        let inner_id = inner_id as usize;
+        if inner_id < INODE_DIRECT_COUNT {
+            self.direct[inner_id]
+        } else if inner_id < INDIRECT1_BOUND {
+            get_block_cache(self.indirect1 as usize, Arc::clone(block_device))
+                .lock()
+                .read(0, |indirect_block: &IndirectBlock| {
+                    indirect_block[inner_id - INODE_DIRECT_COUNT]
+                })
+        } else {
+            let last = inner_id - INDIRECT1_BOUND;
+            let indirect1 = get_block_cache(self.indirect2 as usize, Arc::clone(block_device))
+                .lock()
+                .read(0, |indirect2: &IndirectBlock| {
+                    indirect2[last / INODE_INDIRECT1_COUNT]
+                });
+            get_block_cache(indirect1 as usize, Arc::clone(block_device))
+                .lock()
+                .read(0, |indirect1: &IndirectBlock| {
+                    indirect1[last % INODE_INDIRECT1_COUNT]
+                })
+        }
+    }
+
+    fn data_blocks(&self) -> u32 {
+        Self::_data_blocks(self.size)
+    }
+    fn _data_blocks(size: u32) -> u32 {
+        (size as usize + BLOCK_SZ - 1) as u32 / BLOCK_SZ as u32
+    }
+
+    /// How many blocks (data plus indirect-pointer overhead) a file of
+    /// `size` bytes needs in total
+    pub fn total_blocks(size: u32) -> u32 {
+        let data_blocks = Self::_data_blocks(size) as usize;
+        let mut total = data_blocks;
+        if data_blocks > INODE_DIRECT_COUNT {
+            total += 1;
+        }
+        if data_blocks > INDIRECT1_BOUND {
+            total += 1;
+            total += (data_blocks - INDIRECT1_BOUND + INODE_INDIRECT1_COUNT - 1) / INODE_INDIRECT1_COUNT;
+        }
+        total as u32
+    }
+
+    /// Largest byte size a `DiskInode`'s direct, single-indirect, and
+    /// double-indirect pointers can address between them. `write_at`
+    /// bounds against this the same way `write_at_bounded` already bounds
+    /// against an explicit quota -- past this point there's no block
+    /// pointer left to wire a new block into, quota or not.
+    pub fn max_size() -> u32 {
+        ((INODE_DIRECT_COUNT + INODE_INDIRECT1_COUNT + INODE_INDIRECT2_COUNT) * BLOCK_SZ) as u32
+    }
+
+    /// How many additional blocks must be allocated to grow this inode to
+    /// `new_size`
+    pub fn blocks_num_needed(&self, new_size: u32) -> u32 {
+        assert!(new_size >= self.size);
+        Self::total_blocks(new_size) - Self::total_blocks(self.size)
+    }
+
+    /// Grow to `new_size`, wiring in `new_blocks` (freshly allocated by
+    /// the caller via `blocks_num_needed`) as direct/indirect pointers
+    pub fn increase_size(
+        &mut self,
+        new_size: u32,
+        new_blocks: Vec<u32>,
+        block_device: &Arc<dyn BlockDevice>,
+    ) {
+        let mut current_blocks = self.data_blocks();
+        self.size = new_size;
+        let mut total_blocks = self.data_blocks();
+        let mut new_blocks_iter = new_blocks.into_iter();
+        while current_blocks < total_blocks.min(INODE_DIRECT_COUNT as u32) {
+            self.direct[current_blocks as usize] = new_blocks_iter.next().unwrap();
+            current_blocks += 1;
+        }
+        if total_blocks > INODE_DIRECT_COUNT as u32 {
+            if current_blocks == INODE_DIRECT_COUNT as u32 {
+                self.indirect1 = new_blocks_iter.next().unwrap();
+            }
+            current_blocks -= INODE_DIRECT_COUNT as u32;
+            total_blocks -= INODE_DIRECT_COUNT as u32;
+        } else {
+            return;
+        }
+        get_block_cache(self.indirect1 as usize, Arc::clone(block_device))
+            .lock()
+            .modify(0, |indirect1: &mut IndirectBlock| {
+                while current_blocks < total_blocks.min(INODE_INDIRECT1_COUNT as u32) {
+                    indirect1[current_blocks as usize] = new_blocks_iter.next().unwrap();
+                    current_blocks += 1;
+                }
+            });
+        if total_blocks > INODE_INDIRECT1_COUNT as u32 {
+            if current_blocks == INODE_INDIRECT1_COUNT as u32 {
+                self.indirect2 = new_blocks_iter.next().unwrap();
+            }
+            current_blocks -= INODE_INDIRECT1_COUNT as u32;
+            total_blocks -= INODE_INDIRECT1_COUNT as u32;
+        } else {
+            return;
+        }
+        let mut a0 = current_blocks as usize / INODE_INDIRECT1_COUNT;
+        let mut b0 = current_blocks as usize % INODE_INDIRECT1_COUNT;
+        let a1 = total_blocks as usize / INODE_INDIRECT1_COUNT;
+        let b1 = total_blocks as usize % INODE_INDIRECT1_COUNT;
+        get_block_cache(self.indirect2 as usize, Arc::clone(block_device))
+            .lock()
+            .modify(0, |indirect2: &mut IndirectBlock| {
+                while (a0 < a1) || (a0 == a1 && b0 < b1) {
+                    if b0 == 0 {
+                        indirect2[a0] = new_blocks_iter.next().unwrap();
+                    }
+                    get_block_cache(indirect2[a0] as usize, Arc::clone(block_device))
+                        .lock()
+                        .modify(0, |indirect1: &mut IndirectBlock| {
+                            indirect1[b0] = new_blocks_iter.next().unwrap();
+                        });
+                    b0 += 1;
+                    if b0 == INODE_INDIRECT1_COUNT {
+                        b0 = 0;
+                        a0 += 1;
+                    }
+                }
+            });
+    }
+
+    /// Shrink to `new_size`, returning the block ids freed up so the
+    /// caller can deallocate them
+    pub fn decrease_size(&mut self, new_size: u32, block_device: &Arc<dyn BlockDevice>) -> Vec<u32> {
+        let mut v: Vec<u32> = Vec::new();
+        let data_blocks = self.data_blocks() as usize;
+        self.size = new_size;
+        let new_data_blocks = self.data_blocks() as usize;
+        let mut current_blocks = new_data_blocks;
+        while current_blocks < data_blocks.min(INODE_DIRECT_COUNT) {
+            v.push(self.direct[current_blocks]);
+            self.direct[current_blocks] = 0;
+            current_blocks += 1;
+        }
+        if data_blocks <= INODE_DIRECT_COUNT {
+            return v;
+        }
+        if new_data_blocks <= INODE_DIRECT_COUNT {
+            v.push(self.indirect1);
+        }
+        let data_blocks = data_blocks - INODE_DIRECT_COUNT;
+        let new_data_blocks = new_data_blocks.saturating_sub(INODE_DIRECT_COUNT);
+        if self.indirect1 != 0 {
+            let mut current = new_data_blocks;
+            get_block_cache(self.indirect1 as usize, Arc::clone(block_device))
+                .lock()
+                .modify(0, |indirect1: &mut IndirectBlock| {
+                    while current < data_blocks.min(INODE_INDIRECT1_COUNT) {
+                        v.push(indirect1[current]);
+                        current += 1;
+                    }
+                });
+        }
+        if new_data_blocks == 0 {
+            self.indirect1 = 0;
+        }
+        if data_blocks <= INODE_INDIRECT1_COUNT {
+            return v;
+        }
+        if new_data_blocks <= INODE_INDIRECT1_COUNT {
+            v.push(self.indirect2);
+        }
+        let data_blocks = data_blocks - INODE_INDIRECT1_COUNT;
+        let new_data_blocks = new_data_blocks.saturating_sub(INODE_INDIRECT1_COUNT);
+        assert!(data_blocks <= INODE_INDIRECT2_COUNT);
+        let a0 = new_data_blocks / INODE_INDIRECT1_COUNT;
+        let b0 = new_data_blocks % INODE_INDIRECT1_COUNT;
+        let a1 = data_blocks / INODE_INDIRECT1_COUNT;
+        let b1 = data_blocks % INODE_INDIRECT1_COUNT;
+        if self.indirect2 != 0 {
+            get_block_cache(self.indirect2 as usize, Arc::clone(block_device))
+                .lock()
+                .modify(0, |indirect2: &mut IndirectBlock| {
+                    let mut a = a0;
+                    let mut b = b0;
+                    while a < a1 || (a == a1 && b < b1) {
+                        get_block_cache(indirect2[a] as usize, Arc::clone(block_device))
+                            .lock()
+                            .modify(0, |indirect1: &mut IndirectBlock| {
+                                v.push(indirect1[b]);
+                            });
+                        b += 1;
+                        if b == INODE_INDIRECT1_COUNT {
+                            if a > a0 || b0 == 0 {
+                                v.push(indirect2[a]);
+                            }
+                            b = 0;
+                            a += 1;
+                        }
+                    }
+                    if b1 > 0 && (a1 > a0 || b0 == 0) {
+                        v.push(indirect2[a1]);
+                    }
+                });
+        }
+        if new_data_blocks == 0 {
+            self.indirect2 = 0;
+        }
+        v
+    }
+
+    /// Drop all content, freeing every block this inode addressed
+    pub fn clear_size(&mut self, block_device: &Arc<dyn BlockDevice>) -> Vec<u32> {
+        let mut v: Vec<u32> = Vec::new();
+        let mut data_blocks = self.data_blocks() as usize;
+        self.size = 0;
+        let mut current_blocks = 0usize;
+        while current_blocks < data_blocks.min(INODE_DIRECT_COUNT) {
+            v.push(self.direct[current_blocks]);
+            self.direct[current_blocks] = 0;
+            current_blocks += 1;
+        }
+        if data_blocks > INODE_DIRECT_COUNT {
+            v.push(self.indirect1);
+            data_blocks -= INODE_DIRECT_COUNT;
+            current_blocks = 0;
+        } else {
+            self.indirect1 = 0;
+            return v;
+        }
+        get_block_cache(self.indirect1 as usize, Arc::clone(block_device))
+            .lock()
+            .modify(0, |indirect1: &mut IndirectBlock| {
+                while current_blocks < data_blocks.min(INODE_INDIRECT1_COUNT) {
+                    v.push(indirect1[current_blocks]);
+                    current_blocks += 1;
+                }
+            });
+        self.indirect1 = 0;
+        if data_blocks > INODE_INDIRECT1_COUNT {
+            v.push(self.indirect2);
+            data_blocks -= INODE_INDIRECT1_COUNT;
+        } else {
+            self.indirect2 = 0;
+            return v;
+        }
+        assert!(data_blocks <= INODE_INDIRECT2_COUNT);
+        let a1 = data_blocks / INODE_INDIRECT1_COUNT;
+        let b1 = data_blocks % INODE_INDIRECT1_COUNT;
+        get_block_cache(self.indirect2 as usize, Arc::clone(block_device))
+            .lock()
+            .modify(0, |indirect2: &mut IndirectBlock| {
+                for entry in indirect2.iter().take(a1) {
+                    v.push(*entry);
+                    get_block_cache(*entry as usize, Arc::clone(block_device))
+                        .lock()
+                        .modify(0, |indirect1: &mut IndirectBlock| {
+                            for j in 0..INODE_INDIRECT1_COUNT {
+                                v.push(indirect1[j]);
+                            }
+                        });
+                }
+                if b1 > 0 {
+                    v.push(indirect2[a1]);
+                    get_block_cache(indirect2[a1] as usize, Arc::clone(block_device))
+                        .lock()
+                        .modify(0, |indirect1: &mut IndirectBlock| {
+                            for j in 0..b1 {
+                                v.push(indirect1[j]);
+                            }
+                        });
+                }
+            });
+        self.indirect2 = 0;
+        v
+    }
+
+    /// Note on sparse files: teaching this inode to treat a block index
+    /// of `0` as "hole" (zero-filled on read, allocated lazily on write)
+    /// would mean rewriting this and `write_at` below to check for and
+    /// skip/allocate holes per block, plus `Inode::increase_size` in
+    /// `vfs.rs`, which currently has the caller (via `blocks_num_needed`)
+    /// eagerly allocate every block from the old size up to the new one
+    /// before `increase_size` ever runs -- that eager allocation is what
+    /// would need to become "allocate only the block a `write_at` call
+    /// actually touches". That caller-side decision, and the
+    /// `EasyFileSystem::alloc_data` it's built on, live in `efs.rs`,
+    /// which isn't in this tree. Reworking `read_at`/`write_at` alone
+    /// without it would leave every *non*-sparse file's layout
+    /// inconsistent with what the (unreachable) allocator still hands
+    /// out, so it doesn't land from this file either.
+    ///
+    /// Read up to `buf.len()` bytes starting at `offset`, clamped to this
+    /// inode's `size`; returns the number of bytes actually read
+    pub fn read_at(&self, offset: usize, buf: &mut [u8], block_device: &Arc<dyn BlockDevice>) -> usize {
+        let mut start = offset;
+        let end = (offset + buf.len()).min(self.size as usize);
+        if start >= end {
+            return 0;
+        }
+        let mut start_block = start / BLOCK_SZ;
+        let mut read_size = 0usize;
+        loop {
+            let mut end_current_block = (start / BLOCK_SZ + 1) * BLOCK_SZ;
+            end_current_block = end_current_block.min(end);
+            let block_read_size = end_current_block - start;
+            let dst = &mut buf[read_size..read_size + block_read_size];
+            get_block_cache(
+                self.get_block_id(start_block as u32, block_device) as usize,
+                Arc::clone(block_device),
+            )
+            .lock()
+            .read(0, |data_block: &DataBlock| {
+                let src = &data_block[start % BLOCK_SZ..start % BLOCK_SZ + block_read_size];
+                dst.copy_from_slice(src);
+            });
+            read_size += block_read_size;
+            if end_current_block == end {
+                break;
+            }
+            start_block += 1;
+            start = end_current_block;
+        }
+        read_size
+    }
+
+    /// Write `buf` starting at `offset`; the caller is responsible for
+    /// having grown the inode (via `increase_size`) far enough first
+    pub fn write_at(&self, offset: usize, buf: &[u8], block_device: &Arc<dyn BlockDevice>) -> usize {
+        let mut start = offset;
+        let end = (offset + buf.len()).min(self.size as usize);
+        assert!(start <= end);
+        let mut start_block = start / BLOCK_SZ;
+        let mut write_size = 0usize;
+        loop {
+            let mut end_current_block = (start / BLOCK_SZ + 1) * BLOCK_SZ;
+            end_current_block = end_current_block.min(end);
+            let block_write_size = end_current_block - start;
+            get_block_cache(
+                self.get_block_id(start_block as u32, block_device) as usize,
+                Arc::clone(block_device),
+            )
+            .lock()
+            .modify(0, |data_block: &mut DataBlock| {
+                let src = &buf[write_size..write_size + block_write_size];
+                let dst = &mut data_block[start % BLOCK_SZ..start % BLOCK_SZ + block_write_size];
+                dst.copy_from_slice(src);
+            });
+            write_size += block_write_size;
+            if end_current_block == end {
+                break;
+            }
+            start_block += 1;
+            start = end_current_block;
+        }
+        write_size
+    }
+}
+
+/// Longest name a single [`DirEntry`] can hold
+const NAME_LENGTH_LIMIT: usize = 27;
+
+/// Longest name callers may pass to [`DirEntry::new`], i.e.
+/// `NAME_LENGTH_LIMIT`. Exposed so directory-mutating `Inode` methods can
+/// reject an over-long name up front instead of finding out from
+/// `DirEntry::new` returning `None`.
+pub const NAME_MAX: usize = NAME_LENGTH_LIMIT;
+
+/// A fixed-size directory entry: a null-padded name plus the inode number
+/// it refers to
+#[repr(C)]
+pub struct DirEntry {
+    name: [u8; NAME_LENGTH_LIMIT + 1],
+    inode_number: u32,
+}
+
+/// On-disk size of a [`DirEntry`], i.e. how far apart consecutive entries
+/// are packed in a directory's content
+pub const DIRENT_SZ: usize = 32;
+
+impl DirEntry {
+    /// A zeroed entry, e.g. as a read buffer before `read_at` fills it in
+    pub fn empty() -> Self {
+        Self {
+            name: [0u8; NAME_LENGTH_LIMIT + 1],
+            inode_number: 0,
+        }
+    }
+
+    /// Create an entry pointing `name` at `inode_number`. `None` if
+    /// `name` is longer than [`NAME_MAX`] -- it wouldn't fit in the
+    /// fixed-size `name` field, and silently truncating it risks
+    /// colliding with an existing, differently-named entry.
+    pub fn new(name: &str, inode_number: u32) -> Option<Self> {
+        if name.len() > NAME_LENGTH_LIMIT {
+            return None;
+        }
+        let mut bytes = [0u8; NAME_LENGTH_LIMIT + 1];
+        bytes[..name.len()].copy_from_slice(name.as_bytes());
+        Some(Self {
+            name: bytes,
+            inode_number,
+        })
+    }
+
+    /// View this entry as its raw on-disk bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self as *const _ as usize as *const u8, DIRENT_SZ) }
+    }
+
+    /// View this entry as its raw on-disk bytes, for `read_at` to fill in
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(self as *mut _ as usize as *mut u8, DIRENT_SZ) }
+    }
+
+    /// The entry's name, stopping at the first null byte
+    pub fn name(&self) -> &str {
+        let len = (0usize..NAME_LENGTH_LIMIT + 1)
+            .find(|i| self.name[*i] == 0)
+            .unwrap_or(NAME_LENGTH_LIMIT + 1);
+        core::str::from_utf8(&self.name[..len]).unwrap()
+    }
+
+    /// The inode number this entry refers to
+    pub fn inode_id(&self) -> u32 {
+        self.inode_number
+    }
+}
@@ -1,12 +1,80 @@
 use super::{
     block_cache_sync_all, get_block_cache, BlockDevice, DirEntry, DiskInode, DiskInodeType,
-    EasyFileSystem, DIRENT_SZ,
+    EasyFileSystem, DIRENT_SZ, NAME_MAX,
 };
+use crate::layout::{S_ISGID, S_ISUID};
+use alloc::collections::BTreeMap;
 use alloc::string::String;
-use alloc::sync::Arc;
+use alloc::sync::{Arc, Weak};
 use alloc::vec::Vec;
-use spin::{Mutex, MutexGuard};
+use spin::{Mutex, MutexGuard, RwLock};
 use crate::alloc::string::ToString;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Per-directory name -> inode_id lookup cache, keyed by the directory's
+/// `node_id` rather than by `Inode` instance, so a lookup through one
+/// handle sees entries (or invalidations) made through another handle to
+/// the same on-disk directory. Cleared wholesale for a directory on any
+/// mutation (`create`/`mkdir`/`symlink`/`vfs_link`/`vfs_unlink`/
+/// `vfs_rename`) rather than tracked per-entry -- simpler, and a cache
+/// miss just costs one more linear rescan, not a correctness problem.
+static DENTRY_CACHE: Mutex<BTreeMap<u32, BTreeMap<String, u32>>> = Mutex::new(BTreeMap::new());
+
+/// `Arc<Inode>` handles live on this disk inode's `(block_id,
+/// block_offset)`, kept as `Weak` so a cache entry doesn't itself keep an
+/// otherwise-unreferenced inode alive. Conventionally this would live on
+/// `EasyFileSystem` (one cache per mount); `EasyFileSystem` isn't defined
+/// anywhere in this tree, so it's a module static instead, same as
+/// `DENTRY_CACHE` above and `TICK`/`CASE_INSENSITIVE` below.
+static INODE_CACHE: Mutex<BTreeMap<(usize, usize), Weak<Inode>>> = Mutex::new(BTreeMap::new());
+
+/// Per-inode read/write lock, keyed the same way as [`INODE_CACHE`], so
+/// [`Inode::write_at`] can hold a whole `increase_size`+write sequence
+/// exclusive against other writers *and* readers of the same inode.
+/// `read_at` and `write_at_nosync` also hold `fs.lock()` for the same
+/// span, so reads and writes of unrelated inodes are still serialized
+/// by that lock. Entries are never
+/// removed: a `(block_id, block_offset)` pair is reused by a later
+/// inode only after the earlier one's on-disk slot is freed and
+/// reallocated, at which point the stale lock is harmless (uncontended,
+/// just extra bytes) rather than incorrect.
+static INODE_RWLOCK: Mutex<BTreeMap<(usize, usize), Arc<RwLock<()>>>> = Mutex::new(BTreeMap::new());
+
+/// A monotonically increasing tick, used to timestamp inode accesses
+/// without easy-fs needing a dependency on the kernel's wall-clock timer
+static TICK: AtomicU64 = AtomicU64::new(0);
+
+fn next_tick() -> u64 {
+    TICK.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Whether directory lookups fold ASCII case, a mount-wide toggle.
+/// Conventionally this would be a flag on `EasyFileSystem`, consulted by
+/// `find_inode_id`/`find_dentry`/etc. -- but `EasyFileSystem` has no
+/// `struct` anywhere in this tree, only referenced (see the
+/// `increase_size` doc comment below), so there's no mount to hang the
+/// flag on. It lives as a module-level static instead, the same way
+/// `DENTRY_CACHE` and `TICK` above already stand in for state that would
+/// otherwise belong on a type this crate doesn't define.
+static CASE_INSENSITIVE: AtomicBool = AtomicBool::new(false);
+
+/// Turn case-insensitive directory name comparison on or off, mount-wide.
+/// Creation is unaffected either way -- entries always keep the case they
+/// were created with; this only changes whether a later lookup by a
+/// differently-cased name matches.
+pub fn set_case_insensitive(enabled: bool) {
+    CASE_INSENSITIVE.store(enabled, Ordering::Relaxed);
+}
+
+/// Compare two directory entry names per the current
+/// [`CASE_INSENSITIVE`] setting
+fn names_match(a: &str, b: &str) -> bool {
+    if CASE_INSENSITIVE.load(Ordering::Relaxed) {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
 
 /// Virtual filesystem layer over easy-fs
 pub struct Inode {
@@ -14,6 +82,10 @@ pub struct Inode {
     block_offset: usize,
     fs: Arc<Mutex<EasyFileSystem>>,
     block_device: Arc<dyn BlockDevice>,
+    /// `DiskInode::generation` as of construction, for
+    /// [`Self::stale`]/[`Self::generation_checked`] to catch this handle
+    /// outliving its on-disk slot being freed and reused.
+    generation: u32,
 }
 
 bitflags! {
@@ -26,9 +98,35 @@ bitflags! {
         const DIR   = 0o040000;
         /// ordinary regular file
         const FILE  = 0o100000;
+        /// symbolic link
+        const LINK  = 0o120000;
+    }
+}
+
+/// Failure modes for [`Inode::vfs_link`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkError {
+    /// `old_name` doesn't exist in this directory
+    NotFound,
+    /// `new_name` already exists in this directory
+    AlreadyExists,
+    /// `new_name` is longer than [`NAME_MAX`]
+    NameTooLong,
+}
+
+impl LinkError {
+    /// The bare `isize` this kernel's syscall layer reports filesystem
+    /// failures as; all variants are indistinguishable to a caller still
+    /// on the old `-1`-on-any-failure ABI.
+    pub fn as_isize(self) -> isize {
+        -1
     }
 }
 
+/// How many symlinks [`Inode::resolve`] will follow in a row before
+/// giving up, guarding against a symlink loop
+const MAX_SYMLINK_DEPTH: usize = 8;
+
 impl Inode {
     /// Create a vfs inode
     pub fn new(
@@ -37,24 +135,101 @@ impl Inode {
         fs: Arc<Mutex<EasyFileSystem>>,
         block_device: Arc<dyn BlockDevice>,
     ) -> Self {
+        let generation = get_block_cache(block_id as usize, Arc::clone(&block_device))
+            .lock()
+            .read(block_offset, |disk_inode: &DiskInode| disk_inode.generation);
         Self {
             block_id: block_id as usize,
             block_offset,
             fs,
             block_device,
+            generation,
+        }
+    }
+
+    /// Whether this handle's on-disk slot has since been freed and
+    /// reused for an unrelated file or directory, i.e. whether
+    /// `DiskInode::generation` has moved on from what this `Inode` saw
+    /// at construction.
+    fn stale(&self) -> bool {
+        let current = get_block_cache(self.block_id, Arc::clone(&self.block_device))
+            .lock()
+            .read(self.block_offset, |disk_inode: &DiskInode| disk_inode.generation);
+        current != self.generation
+    }
+    /// Return the live `Arc<Inode>` for `(block_id, block_offset)` if one
+    /// already exists in [`INODE_CACHE`], otherwise build a fresh one via
+    /// [`Inode::new`] and cache it. Every construction site in this file
+    /// that used to call `Arc::new(Self::new(..))` directly goes through
+    /// here instead, so two lookups of the same on-disk inode share one
+    /// object rather than getting independent handles to the same bytes.
+    fn cached(
+        block_id: u32,
+        block_offset: usize,
+        fs: Arc<Mutex<EasyFileSystem>>,
+        block_device: Arc<dyn BlockDevice>,
+    ) -> Arc<Self> {
+        let key = (block_id as usize, block_offset);
+        let mut cache = INODE_CACHE.lock();
+        if let Some(inode) = cache.get(&key).and_then(Weak::upgrade) {
+            return inode;
         }
+        let inode = Arc::new(Self::new(block_id, block_offset, fs, block_device));
+        cache.insert(key, Arc::downgrade(&inode));
+        inode
+    }
+
+    /// This inode's entry in [`INODE_RWLOCK`], creating one on first use.
+    fn rwlock(&self) -> Arc<RwLock<()>> {
+        INODE_RWLOCK
+            .lock()
+            .entry((self.block_id, self.block_offset))
+            .or_insert_with(|| Arc::new(RwLock::new(())))
+            .clone()
     }
-    /// Call a function over a disk inode to read it
+
+    /// Call a function over a disk inode to read it.
+    ///
+    /// With the `diskinode_checksum` feature on, this also verifies
+    /// [`DiskInode::checksum`] before handing `f` the loaded inode.
+    /// Propagating a mismatch as a real `Result` up through every one of
+    /// this file's call sites (all of which currently assume `V` alone)
+    /// would be a much bigger refactor than this check warrants, so a
+    /// mismatch instead logs a warning and panics -- loud and immediate,
+    /// in the same spirit as the `assert!`/`assert_eq!` invariant checks
+    /// already scattered through this file (see `find_inode_id`) -- rather
+    /// than letting a caller walk garbage block pointers.
     fn read_disk_inode<V>(&self, f: impl FnOnce(&DiskInode) -> V) -> V {
         get_block_cache(self.block_id, Arc::clone(&self.block_device))
             .lock()
-            .read(self.block_offset, f)
+            .read(self.block_offset, |disk_inode: &DiskInode| {
+                #[cfg(feature = "diskinode_checksum")]
+                if !disk_inode.verify_checksum() {
+                    warn!(
+                        "easy-fs: checksum mismatch on inode at block {} offset {} -- metadata may be corrupt",
+                        self.block_id, self.block_offset,
+                    );
+                    panic!("easy-fs: DiskInode checksum mismatch");
+                }
+                f(disk_inode)
+            })
     }
-    /// Call a function over a disk inode to modify it
+    /// Call a function over a disk inode to modify it.
+    ///
+    /// With the `diskinode_checksum` feature on, [`DiskInode::checksum`]
+    /// is recomputed and stored after `f` runs, so every write-back
+    /// covers whatever `f` just changed.
     fn modify_disk_inode<V>(&self, f: impl FnOnce(&mut DiskInode) -> V) -> V {
         get_block_cache(self.block_id, Arc::clone(&self.block_device))
             .lock()
-            .modify(self.block_offset, f)
+            .modify(self.block_offset, |disk_inode: &mut DiskInode| {
+                let result = f(disk_inode);
+                #[cfg(feature = "diskinode_checksum")]
+                {
+                    disk_inode.checksum = disk_inode.compute_checksum();
+                }
+                result
+            })
     }
 
     /// Find inode under a disk inode by name
@@ -68,7 +243,7 @@ impl Inode {
                 disk_inode.read_at(DIRENT_SZ * i, dirent.as_bytes_mut(), &self.block_device,),
                 DIRENT_SZ,
             );
-            if dirent.name() == name {
+            if names_match(dirent.name(), name) {
                 return Some(dirent.inode_id());
             }
         }
@@ -86,7 +261,7 @@ impl Inode {
                 disk_inode.read_at(DIRENT_SZ * i, dirent.as_bytes_mut(), &self.block_device,),
                 DIRENT_SZ,
             );
-            if dirent.name() == name {
+            if names_match(dirent.name(), name) {
                 return Some((i, Arc::new(dirent)));
             }
         }
@@ -104,7 +279,7 @@ impl Inode {
                 disk_inode.read_at(DIRENT_SZ * i, dirent.as_bytes_mut(), &self.block_device,),
                 DIRENT_SZ,
             );
-            if dirent.name() == name {
+            if names_match(dirent.name(), name) {
                 return Some(i as u32)
             }
         }
@@ -171,11 +346,35 @@ impl Inode {
         ino
     }
 
+    fn dentry_cache_get(&self, dir_id: u32, name: &str) -> Option<u32> {
+        DENTRY_CACHE.lock().get(&dir_id).and_then(|m| m.get(name)).copied()
+    }
+
+    fn dentry_cache_put(&self, dir_id: u32, name: &str, inode_id: u32) {
+        DENTRY_CACHE
+            .lock()
+            .entry(dir_id)
+            .or_insert_with(BTreeMap::new)
+            .insert(name.to_string(), inode_id);
+    }
+
+    /// Drop every cached entry for this directory, e.g. because it was
+    /// just mutated through `create`/`mkdir`/`symlink`/`vfs_link`/
+    /// `vfs_unlink`/`vfs_rename`. Takes the already-held `fs` guard
+    /// rather than calling `node_id()`, since every call site here runs
+    /// with `self.fs` already locked and `Mutex` isn't reentrant.
+    fn dentry_cache_invalidate(&self, fs: &MutexGuard<EasyFileSystem>) {
+        let dir_id = self.node_id_locked(fs);
+        DENTRY_CACHE.lock().remove(&dir_id);
+    }
+
     ///
     pub fn mode(&self) -> StatMode {
         self.read_disk_inode(|diskinode|{
             if diskinode.is_dir() {
                 StatMode::DIR
+            } else if diskinode.is_symlink() {
+                StatMode::LINK
             } else if diskinode.is_file() {
                 StatMode::FILE
             } else {
@@ -184,20 +383,44 @@ impl Inode {
         })
     }
 
-    /// Find inode under current inode by name
+    /// The [`StatMode`] of the child referenced by `inode_id`, as yielded
+    /// by [`Inode::iter_dir`] -- avoids a second by-name lookup just to
+    /// learn whether a directory entry is itself a directory, file, or
+    /// symlink.
+    pub fn mode_of_child(&self, inode_id: u32) -> StatMode {
+        let fs = self.fs.lock();
+        let (block_id, block_offset) = fs.get_disk_inode_pos(inode_id);
+        drop(fs);
+        Inode::new(block_id, block_offset, self.fs.clone(), self.block_device.clone()).mode()
+    }
+
+    /// Find inode under current inode by name. `name` may be a single
+    /// component or a `/`-separated path, in which case each component is
+    /// resolved in turn via [`Inode::resolve`] (symlinks included).
     pub fn find(&self, name: &str) -> Option<Arc<Inode>> {
+        if name.contains('/') {
+            return self.resolve(name);
+        }
+        let dir_id = self.node_id();
+        let inode_id = match self.dentry_cache_get(dir_id, name) {
+            Some(id) => id,
+            None => {
+                let id = self
+                    .iter_dir()
+                    .find(|(dent, _)| names_match(dent.name(), name))
+                    .map(|(dent, _)| dent.inode_id())?;
+                self.dentry_cache_put(dir_id, name, id);
+                id
+            }
+        };
         let fs = self.fs.lock();
-        self.read_disk_inode(|disk_inode| {
-            self.find_inode_id(name, disk_inode).map(|inode_id| {
-                let (block_id, block_offset) = fs.get_disk_inode_pos(inode_id);
-                Arc::new(Self::new(
-                    block_id,
-                    block_offset,
-                    self.fs.clone(),
-                    self.block_device.clone(),
-                ))
-            })
-        })
+        let (block_id, block_offset) = fs.get_disk_inode_pos(inode_id);
+        Some(Self::cached(
+            block_id,
+            block_offset,
+            self.fs.clone(),
+            self.block_device.clone(),
+        ))
     }
 
     /// Find inode under current inode by name
@@ -205,12 +428,12 @@ impl Inode {
         self.read_disk_inode(|disk_inode| {
             self.find_inode_id(name, disk_inode).map(|inode_id| {
                 let (block_id, block_offset) = fs.get_disk_inode_pos(inode_id);
-                Arc::new(Self::new(
+                Self::cached(
                     block_id,
                     block_offset,
                     self.fs.clone(),
                     self.block_device.clone(),
-                ))
+                )
             })
         })
     }
@@ -260,11 +483,71 @@ impl Inode {
 
     /// Find inode under current inode by name
     pub fn find_by_id(&self, id: u32) -> Vec<String> {
-        //self.show_dentries();
+        self.iter_dir()
+            .filter(|(dent, _)| dent.inode_id() == id)
+            .map(|(dent, _)| dent.name().to_string())
+            .collect()
+    }
+
+    /// Like [`Inode::find_by_id`], but also descends into every
+    /// subdirectory beneath `self` instead of only looking at `self`'s
+    /// own entries -- a link tucked away in a subdirectory is invisible
+    /// to `find_by_id` called on some ancestor, since it never looks past
+    /// the one directory it's called on. Matches come back as
+    /// `child/grandchild`-style paths relative to `self` rather than bare
+    /// names, so a hit several levels down can't be confused with one of
+    /// the same name sitting elsewhere in the subtree.
+    ///
+    /// This is a full walk of every directory under `self`, not a single
+    /// directory's worth of entries like `find_by_id` -- O(dentries in
+    /// the whole subtree) instead of O(dentries in one directory), so it
+    /// isn't a drop-in replacement for `find_by_id`'s callers (like
+    /// `sys_getcwd`, which only ever wants the immediate parent's name
+    /// for the child it already has a handle to, and would pay for a
+    /// needless subtree walk to get the same answer). Exists for a
+    /// caller that genuinely needs an id resolved against the whole tree
+    /// under `self` -- an explicit `nlink` field tracked on the inode
+    /// itself (as `sys_fstat` already does) is the better fix where one
+    /// is available, since it's O(1) instead of O(subtree) per lookup.
+    pub fn find_by_id_recursive(&self, id: u32) -> Vec<String> {
+        let mut results = Vec::new();
+        // Collected up front: `iter_dir` holds `fs.lock()`, which the
+        // `find`/`mode` calls below take again.
+        let entries: Vec<DirEntry> = self.iter_dir().map(|(dent, _)| dent).collect();
+        for dent in entries {
+            if dent.name() == "." || dent.name() == ".." {
+                continue;
+            }
+            if dent.inode_id() == id {
+                results.push(dent.name().to_string());
+            }
+            if let Some(child) = self.find(dent.name()) {
+                if child.mode() == StatMode::DIR {
+                    for name in child.find_by_id_recursive(id) {
+                        results.push(alloc::format!("{}/{}", dent.name(), name));
+                    }
+                }
+            }
+        }
+        results
+    }
+
+    /// Lazily iterate this directory's dentries, one `DIRENT_SZ` chunk
+    /// read per `next()` instead of materializing the whole listing.
+    /// Holds the filesystem lock for the iterator's lifetime so
+    /// concurrent allocation can't shift entries out from under it.
+    pub fn iter_dir(&self) -> DirEntryIter<'_> {
         let fs = self.fs.lock();
-        self.read_disk_inode(|disk_inode| {
-            self.find_inode_by_id(id, disk_inode)
-        })
+        let file_count = self.read_disk_inode(|disk_inode| {
+            assert!(disk_inode.is_dir());
+            (disk_inode.size as usize) / DIRENT_SZ
+        });
+        DirEntryIter {
+            _fs: fs,
+            inode: self,
+            cursor: 0,
+            file_count,
+        }
     }
 
     /// Find inode under current inode by name
@@ -277,6 +560,18 @@ impl Inode {
     }
 
     /// Increase the size of a disk inode
+    ///
+    /// Rolling back the blocks already grabbed if the device runs out
+    /// partway can't land here: `fs.alloc_data()` returns a bare `u32`,
+    /// not an `Option<u32>`/`Result`, so whatever it does on exhaustion
+    /// (conventionally panicking via an `unwrap()` on the free-block
+    /// bitmap inside `EasyFileSystem::alloc_data`, which isn't in this
+    /// tree) happens before this loop ever gets a failed id back to
+    /// notice and free the rest of `v` for. Surfacing a recoverable
+    /// out-of-space error needs `alloc_data` itself to stop panicking and
+    /// start returning `None`, and `EasyFileSystem` isn't defined
+    /// anywhere in this tree, only referenced (see the `MutexGuard`
+    /// parameter below) -- there's no impl block here to change.
     fn increase_size(
         &self,
         new_size: u32,
@@ -313,13 +608,7 @@ impl Inode {
     /// Create inode under current inode by name
     pub fn create(&self, name: &str) -> Option<Arc<Inode>> {
         let mut fs = self.fs.lock();
-        let op = |root_inode: &DiskInode| {
-            // assert it is a directory
-            assert!(root_inode.is_dir());
-            // has the file been created?
-            self.find_inode_id(name, root_inode)
-        };
-        if self.read_disk_inode(op).is_some() {
+        if name.len() > NAME_MAX {
             return None;
         }
         // create a new file
@@ -332,38 +621,385 @@ impl Inode {
             .modify(new_inode_block_offset, |new_inode: &mut DiskInode| {
                 new_inode.initialize(DiskInodeType::File);
             });
-        self.modify_disk_inode(|root_inode| {
+        // The existence check and the dirent append used to be two
+        // separate `get_block_cache(..).lock()` critical sections (one
+        // `read_disk_inode`, one `modify_disk_inode`), which left a
+        // window between them for a second `create` of the same `name`
+        // to also pass the check. Folding both into one
+        // `modify_disk_inode` call makes "does `name` exist, and if not
+        // append it" atomic against the root block's own lock, not just
+        // against `fs`.
+        let created = self.modify_disk_inode(|root_inode| {
+            // assert it is a directory
+            assert!(root_inode.is_dir());
+            // has the file been created (by us, just now, or by a racing
+            // creator that got here first)?
+            if self.find_inode_id(name, root_inode).is_some() {
+                return false;
+            }
             // append file in the dirent
             let file_count = (root_inode.size as usize) / DIRENT_SZ;
             let new_size = (file_count + 1) * DIRENT_SZ;
             // increase size
             self.increase_size(new_size as u32, root_inode, &mut fs);
             // write dirent
-            let dirent = DirEntry::new(name, new_inode_id);
+            let dirent = DirEntry::new(name, new_inode_id).unwrap();
             root_inode.write_at(
                 file_count * DIRENT_SZ,
                 dirent.as_bytes(),
                 &self.block_device,
             );
+            true
         });
+        if !created {
+            // `name` appeared between us deciding to create it and
+            // taking the root directory's lock. The inode we allocated
+            // above goes unused -- easy-fs has no inode free-list to
+            // return it to, the same as every other abandoned allocation
+            // in this file.
+            return None;
+        }
 
         let (block_id, block_offset) = fs.get_disk_inode_pos(new_inode_id);
+        self.dentry_cache_invalidate(&fs);
         block_cache_sync_all();
         // return inode
-        Some(Arc::new(Self::new(
+        Some(Self::cached(
             block_id,
             block_offset,
             self.fs.clone(),
             self.block_device.clone(),
-        )))
+        ))
         // release efs lock automatically by compiler
     }
 
+    /// Atomically find-or-create `name` in this directory. `create`'s own
+    /// doc comment above already closes the race between two concurrent
+    /// creators inside the directory's own `modify_disk_inode` section --
+    /// at most one caller's `create` actually appends the dirent, and the
+    /// rest see it return `None`. This just adds the fallback lookup so
+    /// the loser of that race gets a handle to the winner's inode back
+    /// instead of `None`, the same `Arc<Inode>` thanks to
+    /// [`Inode::cached`] (both `create` and `find` resolve through it).
     ///
-    pub fn vfs_link(&self, old_name: &str, new_name: &str) -> isize {
+    /// Whoever owns `open_file` (in `crate::fs`, not in this tree; see
+    /// `os/src/syscall/fs.rs`'s O_CREATE notes on `sys_open`) should call
+    /// this instead of its own separate find-then-create for `O_CREATE`
+    /// opens, to avoid reintroducing the window this closes.
+    pub fn find_or_create(&self, name: &str) -> Option<Arc<Inode>> {
+        if let Some(inode) = self.create(name) {
+            return Some(inode);
+        }
+        self.find(name)
+    }
+
+    /// Size of the file's content in bytes
+    pub fn size(&self) -> u64 {
+        self.read_disk_inode(|disk_inode| disk_inode.size as u64)
+    }
+
+    /// Number of hard links to this inode
+    pub fn nlink(&self) -> u32 {
+        self.read_disk_inode(|disk_inode| disk_inode.nlink)
+    }
+
+    /// Owning user id
+    pub fn uid(&self) -> u32 {
+        self.read_disk_inode(|disk_inode| disk_inode.uid)
+    }
+
+    /// Owning group id
+    pub fn gid(&self) -> u32 {
+        self.read_disk_inode(|disk_inode| disk_inode.gid)
+    }
+
+    /// Raw on-disk mode: the permission and setuid/setgid bits plus the
+    /// inode type bits
+    pub fn perm(&self) -> u32 {
+        self.read_disk_inode(|disk_inode| disk_inode.mode)
+    }
+
+    /// `(atime, mtime, ctime)`, in the same monotonic tick units used
+    /// elsewhere in the kernel
+    pub fn times(&self) -> (u64, u64, u64) {
+        self.read_disk_inode(|disk_inode| (disk_inode.atime, disk_inode.mtime, disk_inode.ctime))
+    }
+
+    /// Change the permission bits, leaving the inode type bits untouched
+    pub fn chmod(&self, mode: u32) {
+        self.modify_disk_inode(|disk_inode| {
+            disk_inode.mode = (disk_inode.mode & !0o7777) | (mode & 0o7777);
+        });
+    }
+
+    /// Change the owning user/group
+    pub fn chown(&self, uid: u32, gid: u32) {
+        self.modify_disk_inode(|disk_inode| {
+            disk_inode.uid = uid;
+            disk_inode.gid = gid;
+        });
+    }
+
+    /// Explicitly set the access/modify/change timestamps
+    pub fn set_times(&self, atime: u64, mtime: u64, ctime: u64) {
+        self.modify_disk_inode(|disk_inode| {
+            disk_inode.atime = atime;
+            disk_inode.mtime = mtime;
+            disk_inode.ctime = ctime;
+        });
+    }
+
+    /// Evaluate the owner/group/other permission triple in this inode's
+    /// mode against a requesting credential. `mask` is an rwx bitmask
+    /// (e.g. `0o4` for read); returns whether every bit in `mask` is
+    /// granted. Uid `0` always passes, matching root's usual bypass.
+    pub fn check_access(&self, uid: u32, gid: u32, mask: u32) -> bool {
+        if uid == 0 {
+            return true;
+        }
+        let (owner_uid, owner_gid, mode) =
+            self.read_disk_inode(|disk_inode| (disk_inode.uid, disk_inode.gid, disk_inode.mode));
+        let perm = if uid == owner_uid {
+            (mode >> 6) & 0o7
+        } else if gid == owner_gid {
+            (mode >> 3) & 0o7
+        } else {
+            mode & 0o7
+        };
+        perm & mask == mask
+    }
+
+    /// Whether the owner permission bits in this inode's mode grant every
+    /// bit in `mask` (e.g. `0o4` for read, `0o2` for write). A single-user
+    /// shortcut for [`Inode::check_access`]: there's no per-task
+    /// credential anywhere in this tree to plug in as `uid`/`gid`, so
+    /// every caller is treated as the inode's owner, per this being a
+    /// single-user OS.
+    pub fn owner_perm(&self, mask: u32) -> bool {
+        let mode = self.read_disk_inode(|disk_inode| disk_inode.mode);
+        (mode >> 6) & mask == mask
+    }
+
+    /// Clear the setuid/setgid bits, e.g. because a non-owner just wrote
+    /// to this inode
+    fn clear_suid_sgid(&self) {
+        self.modify_disk_inode(|disk_inode| {
+            disk_inode.mode &= !(S_ISUID | S_ISGID);
+        });
+    }
+
+    /// Write data to current inode on behalf of `writer_uid`, clearing
+    /// the setuid/setgid bits unless the writer owns the inode or is
+    /// privileged (uid `0`)
+    pub fn write_at_checked(&self, offset: usize, buf: &[u8], writer_uid: u32) -> usize {
+        let owner_uid = self.uid();
+        let size = self.write_at(offset, buf);
+        if writer_uid != 0 && writer_uid != owner_uid {
+            self.clear_suid_sgid();
+        }
+        size
+    }
+
+    /// Create a directory under current inode by name, seeded with `.`
+    /// and `..` dentries so it can hold its own children
+    pub fn mkdir(&self, name: &str) -> Option<Arc<Inode>> {
         let mut fs = self.fs.lock();
+        let op = |root_inode: &DiskInode| {
+            assert!(root_inode.is_dir());
+            self.find_inode_id(name, root_inode)
+        };
+        if self.read_disk_inode(op).is_some() {
+            return None;
+        }
+        if name.len() > NAME_MAX {
+            return None;
+        }
+        let new_inode_id = fs.alloc_inode();
+        let (new_inode_block_id, new_inode_block_offset) = fs.get_disk_inode_pos(new_inode_id);
+        get_block_cache(new_inode_block_id as usize, Arc::clone(&self.block_device))
+            .lock()
+            .modify(new_inode_block_offset, |new_inode: &mut DiskInode| {
+                new_inode.initialize(DiskInodeType::Directory);
+            });
+
+        let new_dir = Inode::new(
+            new_inode_block_id,
+            new_inode_block_offset,
+            self.fs.clone(),
+            self.block_device.clone(),
+        );
+        let self_id = self.node_id_locked(&fs);
+        new_dir.modify_disk_inode(|dir_inode| {
+            new_dir.increase_size(2 * DIRENT_SZ as u32, dir_inode, &mut fs);
+            dir_inode.write_at(0, DirEntry::new(".", new_inode_id).unwrap().as_bytes(), &new_dir.block_device);
+            dir_inode.write_at(
+                DIRENT_SZ,
+                DirEntry::new("..", self_id).unwrap().as_bytes(),
+                &new_dir.block_device,
+            );
+            // `.` is itself a dirent pointing back at this inode, on top
+            // of the one the parent just got, so a fresh directory starts
+            // at nlink 2, the same as every Unix filesystem's `mkdir`.
+            dir_inode.nlink = 2;
+        });
+
+        self.modify_disk_inode(|root_inode| {
+            let file_count = (root_inode.size as usize) / DIRENT_SZ;
+            let new_size = (file_count + 1) * DIRENT_SZ;
+            self.increase_size(new_size as u32, root_inode, &mut fs);
+            let dirent = DirEntry::new(name, new_inode_id).unwrap();
+            root_inode.write_at(file_count * DIRENT_SZ, dirent.as_bytes(), &self.block_device);
+            // The new child's `..` entry is one more dirent pointing at
+            // this (the parent) inode.
+            root_inode.nlink += 1;
+        });
+
+        self.dentry_cache_invalidate(&fs);
+        block_cache_sync_all();
+        Some(Self::cached(
+            new_inode_block_id,
+            new_inode_block_offset,
+            self.fs.clone(),
+            self.block_device.clone(),
+        ))
+    }
+
+    /// Resolve a `/`-separated path against this inode, walking one
+    /// component at a time, descending into directories, and
+    /// transparently following symlinks (up to [`MAX_SYMLINK_DEPTH`] in a
+    /// row, to bound loops) both mid-path and for the final component
+    pub fn resolve(&self, path: &str) -> Option<Arc<Inode>> {
+        self.resolve_at_depth(path, 0)
+    }
+
+    fn resolve_at_depth(&self, path: &str, depth: usize) -> Option<Arc<Inode>> {
+        if depth > MAX_SYMLINK_DEPTH {
+            return None;
+        }
+        let mut current = Self::cached(
+            self.block_id as u32,
+            self.block_offset,
+            self.fs.clone(),
+            self.block_device.clone(),
+        );
+        // The directory `current` was looked up in, i.e. where a symlink
+        // found as `current` sits.
+        let mut parent = current.clone();
+        for component in path.split('/').filter(|c| !c.is_empty()) {
+            // `.` is always a no-op, POSIX-style, without even needing a
+            // literal `.` dentry to resolve through.
+            if component == "." {
+                continue;
+            }
+            if current.mode() == StatMode::LINK {
+                // Follow relative to the symlink's own containing
+                // directory (`parent`), not the original receiver
+                // (`self`) -- a symlink midway through the path can point
+                // at something that only resolves correctly from where it
+                // actually sits.
+                current = parent.resolve_at_depth(&current.readlink(), depth + 1)?;
+            }
+            if current.mode() != StatMode::DIR {
+                return None;
+            }
+            if component == ".." {
+                // Clamp at the root instead of underflowing: a `..` that
+                // finds no parent dentry (the root has none) just stays
+                // put, the same way the real filesystem root treats `..`
+                // as itself.
+                current = current.find("..").unwrap_or(current);
+                continue;
+            }
+            parent = current.clone();
+            current = current.find(component)?;
+        }
+        if current.mode() == StatMode::LINK {
+            current = parent.resolve_at_depth(&current.readlink(), depth + 1)?;
+        }
+        Some(current)
+    }
+
+    /// Create a symlink under current inode by name, storing `target` as
+    /// its content via the regular data-block write path
+    pub fn symlink(&self, name: &str, target: &str) -> Option<Arc<Inode>> {
+        let mut fs = self.fs.lock();
+        let op = |root_inode: &DiskInode| {
+            assert!(root_inode.is_dir());
+            self.find_inode_id(name, root_inode)
+        };
+        if self.read_disk_inode(op).is_some() {
+            return None;
+        }
+        if name.len() > NAME_MAX {
+            return None;
+        }
+        let new_inode_id = fs.alloc_inode();
+        let (new_inode_block_id, new_inode_block_offset) = fs.get_disk_inode_pos(new_inode_id);
+        get_block_cache(new_inode_block_id as usize, Arc::clone(&self.block_device))
+            .lock()
+            .modify(new_inode_block_offset, |new_inode: &mut DiskInode| {
+                new_inode.initialize(DiskInodeType::SymLink);
+            });
+
+        let link_inode = Inode::new(
+            new_inode_block_id,
+            new_inode_block_offset,
+            self.fs.clone(),
+            self.block_device.clone(),
+        );
+        link_inode.modify_disk_inode(|disk_inode| {
+            link_inode.increase_size(target.len() as u32, disk_inode, &mut fs);
+            disk_inode.write_at(0, target.as_bytes(), &link_inode.block_device);
+        });
+
+        self.modify_disk_inode(|root_inode| {
+            let file_count = (root_inode.size as usize) / DIRENT_SZ;
+            let new_size = (file_count + 1) * DIRENT_SZ;
+            self.increase_size(new_size as u32, root_inode, &mut fs);
+            let dirent = DirEntry::new(name, new_inode_id).unwrap();
+            root_inode.write_at(file_count * DIRENT_SZ, dirent.as_bytes(), &self.block_device);
+        });
+
+        self.dentry_cache_invalidate(&fs);
+        block_cache_sync_all();
+        Some(Self::cached(
+            new_inode_block_id,
+            new_inode_block_offset,
+            self.fs.clone(),
+            self.block_device.clone(),
+        ))
+    }
+
+    /// Read back the target path stored in a symlink inode
+    pub fn readlink(&self) -> String {
+        let size = self.size() as usize;
+        let mut buf = alloc::vec![0u8; size];
+        self.read_at(0, &mut buf);
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+
+    ///
+    pub fn vfs_link(&self, old_name: &str, new_name: &str) -> Result<(), LinkError> {
+        if new_name.len() > NAME_MAX {
+            return Err(LinkError::NameTooLong);
+        }
+        let mut fs = self.fs.lock();
+        if self.lookup_locked(new_name, &fs).is_some() {
+            return Err(LinkError::AlreadyExists);
+        }
         if let Some((_, src_dent)) = self.lookup_locked(old_name, &fs) {
             trace!("linking {}@{} to {}", old_name, src_dent.inode_id(), new_name);
+            let (src_block_id, src_block_offset) = fs.get_disk_inode_pos(src_dent.inode_id());
+            let src_inode = Inode::new(
+                src_block_id,
+                src_block_offset,
+                self.fs.clone(),
+                self.block_device.clone(),
+            );
+            src_inode.modify_disk_inode(|disk_inode| {
+                disk_inode.nlink += 1;
+            });
+
             self.modify_disk_inode(|root_inode| {
                 // append file in the dirent
                 let file_count = (root_inode.size as usize) / DIRENT_SZ;
@@ -371,40 +1007,51 @@ impl Inode {
                 // increase size
                 self.increase_size(new_size as u32, root_inode, &mut fs);
                 // write dirent
-                let dirent = DirEntry::new(new_name, src_dent.inode_id());
+                let dirent = DirEntry::new(new_name, src_dent.inode_id()).unwrap();
                 root_inode.write_at(file_count * DIRENT_SZ, dirent.as_bytes(), &self.block_device);
             });
-    
+
+            self.dentry_cache_invalidate(&fs);
             block_cache_sync_all();
-            0
+            Ok(())
         } else {
-            -1
+            Err(LinkError::NotFound)
         }
     }
 
     ///
     pub fn vfs_unlink(&self, name: &str) -> isize {
         let mut fs = self.fs.lock();
-        trace!("111111111");
         if let Some((idx, dent)) = self.lookup_locked(name, &fs) {
-            let names = self.find_by_id_locked(dent.inode_id(), &fs);
-            let links = names.len();
-            if links == 1 {
-                let (block_id, block_offset) = fs.get_disk_inode_pos(dent.inode_id());
-                let inode = Inode::new(
-                                block_id,
-                                block_offset,
-                                self.fs.clone(),
-                                self.block_device.clone(),
-                            );
-                trace!("222222222");
+            let (block_id, block_offset) = fs.get_disk_inode_pos(dent.inode_id());
+            let inode = Inode::new(
+                            block_id,
+                            block_offset,
+                            self.fs.clone(),
+                            self.block_device.clone(),
+                        );
+            // Refuse to unlink a non-empty directory, the same way a real
+            // `unlink`/`rmdir` split would -- every directory carries `.`
+            // and `..`, so anything beyond those two entries means it
+            // still has children.
+            let is_nonempty_dir = inode.read_disk_inode(|disk_inode| {
+                disk_inode.is_dir() && (disk_inode.size as usize) / DIRENT_SZ > 2
+            });
+            if is_nonempty_dir {
+                return -1;
+            }
+            // `nlink` is maintained incrementally by `vfs_link`/`vfs_unlink`,
+            // so dropping to zero links no longer requires scanning every
+            // dentry in every directory that might reference this inode.
+            let remaining = inode.modify_disk_inode(|disk_inode| {
+                disk_inode.nlink -= 1;
+                disk_inode.nlink
+            });
+            if remaining == 0 {
                 inode.modify_disk_inode(|disk_inode| {
                     disk_inode.clear_size(&self.block_device);
-                });                
-                trace!("3333333333");
+                });
             }
-
-            trace!("444444444444");
             self.modify_disk_inode(|root_inode| {
                 // append file in the dirent
                 let file_count = (root_inode.size as usize) / DIRENT_SZ;
@@ -421,12 +1068,11 @@ impl Inode {
                 }
 
                 let new_size = (file_count - 1) * DIRENT_SZ;
-                // increase size
-                trace!("555555555555");
+                // decrease size
                 self.decrease_size(new_size as u32, root_inode, &mut fs);
-            });    
-            trace!("6666666666");
-        
+            });
+
+            self.dentry_cache_invalidate(&fs);
             block_cache_sync_all();
             0
         } else {
@@ -434,56 +1080,287 @@ impl Inode {
         }
     }
 
-    /// List inodes under current inode
-    pub fn ls(&self) -> Vec<String> {
-        let _fs = self.fs.lock();
-        self.read_disk_inode(|disk_inode| {
-            let file_count = (disk_inode.size as usize) / DIRENT_SZ;
-            let mut v: Vec<String> = Vec::new();
-            for i in 0..file_count {
-                let mut dirent = DirEntry::empty();
+    /// Move `old_name` out of this directory and into `new_dir` as
+    /// `new_name`, without touching the underlying inode or its `nlink`
+    /// -- unlike `vfs_unlink` followed by `vfs_link`, the moved entry
+    /// never drops to zero links along the way, so this also works when
+    /// there's only the one link. `self` and `new_dir` may be the same
+    /// directory, e.g. a same-directory rename.
+    pub fn vfs_rename(&self, old_name: &str, new_dir: &Inode, new_name: &str) -> Result<(), LinkError> {
+        if new_name.len() > NAME_MAX {
+            return Err(LinkError::NameTooLong);
+        }
+        let mut fs = self.fs.lock();
+        if new_dir.lookup_locked(new_name, &fs).is_some() {
+            return Err(LinkError::AlreadyExists);
+        }
+        let (old_idx, old_dent) = match self.lookup_locked(old_name, &fs) {
+            Some(v) => v,
+            None => return Err(LinkError::NotFound),
+        };
+        // Link the new name in before unlinking the old one, so a crash
+        // mid-rename leaves the entry reachable from at least one of the
+        // two directories rather than neither.
+        new_dir.modify_disk_inode(|dir_inode| {
+            let file_count = (dir_inode.size as usize) / DIRENT_SZ;
+            let new_size = (file_count + 1) * DIRENT_SZ;
+            new_dir.increase_size(new_size as u32, dir_inode, &mut fs);
+            let dirent = DirEntry::new(new_name, old_dent.inode_id()).unwrap();
+            dir_inode.write_at(file_count * DIRENT_SZ, dirent.as_bytes(), &new_dir.block_device);
+        });
+        // Same swap-with-last-and-shrink compaction `vfs_unlink` uses to
+        // drop the old dentry.
+        self.modify_disk_inode(|root_inode| {
+            let file_count = (root_inode.size as usize) / DIRENT_SZ;
+            if old_idx < file_count - 1 {
+                let mut last_dent = DirEntry::empty();
+                assert_eq!(
+                    root_inode.read_at((file_count - 1) * DIRENT_SZ, last_dent.as_bytes_mut(), &self.block_device),
+                    DIRENT_SZ,
+                );
                 assert_eq!(
-                    disk_inode.read_at(i * DIRENT_SZ, dirent.as_bytes_mut(), &self.block_device,),
+                    root_inode.write_at(old_idx * DIRENT_SZ, last_dent.as_bytes(), &self.block_device),
                     DIRENT_SZ,
                 );
-                v.push(String::from(dirent.name()));
             }
-            v
-        })
+            let new_size = (file_count - 1) * DIRENT_SZ;
+            self.decrease_size(new_size as u32, root_inode, &mut fs);
+        });
+        self.dentry_cache_invalidate(&fs);
+        new_dir.dentry_cache_invalidate(&fs);
+        block_cache_sync_all();
+        Ok(())
     }
-    /// Read data from current inode
+
+    /// List inodes under current inode
+    pub fn ls(&self) -> Vec<String> {
+        self.iter_dir().map(|(dent, _)| String::from(dent.name())).collect()
+    }
+    /// A `read_at_seq` path (or a per-inode access-pattern detector) that
+    /// notices consecutive block ids and prefetches the next few blocks
+    /// into the cache ahead of need can't be added here: the prefetch
+    /// itself is a cache operation -- populate block N+1's cache entry
+    /// without anyone having asked to read it yet -- and that's
+    /// `get_block_cache`'s call to make, not `Inode::read_at`'s.
+    /// `BlockCache`/`get_block_cache` have no `struct`/`fn` anywhere in
+    /// this tree (same gap as the LRU-eviction and `sync_ordered` notes
+    /// below), so there's nothing here to bound a prefetch window on or
+    /// to check "still in use" against before evicting to make room for
+    /// one.
+    ///
+    /// Read data from current inode. Takes this inode's [`RwLock`] in
+    /// shared mode for the duration, so a concurrent [`Inode::write_at`]
+    /// on the same inode can't be interleaved mid-read. `fs.lock()` is
+    /// held for the same span, so reads and writes of other inodes wait
+    /// on it too.
+    ///
+    /// Returns `0` without touching the block if [`Self::stale`] --
+    /// this handle's on-disk slot was freed (`vfs_unlink`) and reused
+    /// for an unrelated file since this `Inode` was constructed -- the
+    /// same "nothing to read" signal a zero-length `buf` would already
+    /// produce, rather than silently reading someone else's data.
     pub fn read_at(&self, offset: usize, buf: &mut [u8]) -> usize {
+        let _rw = self.rwlock();
+        let _rw = _rw.read();
         let _fs = self.fs.lock();
-        self.read_disk_inode(|disk_inode| disk_inode.read_at(offset, buf, &self.block_device))
+        if self.stale() {
+            return 0;
+        }
+        self.modify_disk_inode(|disk_inode| {
+            let len = disk_inode.read_at(offset, buf, &self.block_device);
+            disk_inode.atime = next_tick();
+            len
+        })
     }
 
     /// Read data from current inode
     pub fn read_at_locked(&self, offset: usize, buf: &mut [u8], fs: &MutexGuard<EasyFileSystem>) -> usize {
         //let _fs = self.fs.lock();
-        self.read_disk_inode(|disk_inode| disk_inode.read_at(offset, buf, &self.block_device))
+        self.modify_disk_inode(|disk_inode| {
+            let len = disk_inode.read_at(offset, buf, &self.block_device);
+            disk_inode.atime = next_tick();
+            len
+        })
     }
 
-    /// Write data to current inode
+    /// Write data to current inode. Bounded by [`DiskInode::max_size`]
+    /// via [`Inode::write_at_bounded`] -- past that point there's no
+    /// direct/indirect block pointer left to address a new block with,
+    /// so a write reaching that far is short rather than silently
+    /// running past the inode's addressing capacity.
     pub fn write_at(&self, offset: usize, buf: &[u8]) -> usize {
+        self.write_at_bounded(offset, buf, DiskInode::max_size() as usize)
+    }
+
+    /// Like [`Inode::write_at`], but never grows the inode past `max_size`
+    /// bytes: if `offset + buf.len()` would exceed it, only the leading
+    /// bytes that fit are written, and the actual count written is
+    /// returned instead of `buf.len()`. Needed for quota-limited files and
+    /// append-only logs, where silently growing past a cap is wrong.
+    pub fn write_at_bounded(&self, offset: usize, buf: &[u8], max_size: usize) -> usize {
+        if offset >= max_size {
+            return 0;
+        }
+        let buf = &buf[..buf.len().min(max_size - offset)];
+        let size = self.write_at_nosync(offset, buf);
+        block_cache_sync_all();
+        size
+    }
+
+    /// Like [`Inode::write_at`], but skips the trailing
+    /// `block_cache_sync_all()` -- every write flushing the *entire*
+    /// block cache makes many small writes (e.g. append-only logging)
+    /// far slower than it needs to be. Callers using this are
+    /// responsible for flushing eventually themselves, e.g. via
+    /// `sys_fsync` or on `File` close.
+    ///
+    /// Holds this inode's [`RwLock`] exclusively across the whole
+    /// `increase_size`+write sequence below, on top of (not instead of)
+    /// `fs.lock()`, which is held for the same span. `fs.lock()` already
+    /// serializes this against every other mutation in the filesystem, so
+    /// two writers to *different* inodes do not run concurrently either;
+    /// the per-inode lock additionally excludes `read_at` on the same
+    /// inode for the whole `increase_size`+write.
+    ///
+    /// Like [`Inode::read_at`], returns `0` without writing anything if
+    /// [`Self::stale`] -- see its doc comment.
+    pub fn write_at_nosync(&self, offset: usize, buf: &[u8]) -> usize {
+        let _rw = self.rwlock();
+        let _rw = _rw.write();
         let mut fs = self.fs.lock();
-        let size = self.modify_disk_inode(|disk_inode| {
+        if self.stale() {
+            return 0;
+        }
+        self.modify_disk_inode(|disk_inode| {
             self.increase_size((offset + buf.len()) as u32, disk_inode, &mut fs);
-            disk_inode.write_at(offset, buf, &self.block_device)
-        });
+            let size = disk_inode.write_at(offset, buf, &self.block_device);
+            disk_inode.mtime = next_tick();
+            size
+        })
+    }
+
+    /// Flush every block cached for this filesystem. A real per-inode
+    /// flush would need the block cache to track which cached blocks
+    /// belong to which inode; lacking that, `sys_fsync` falls back to
+    /// flushing everything, same as every other mutating method here
+    /// already does.
+    ///
+    /// A dirty bit and a `sync_block(block_id)`/`flush_dirty()` API would
+    /// also belong on the block cache, for the same reason: `BlockCache`
+    /// and `get_block_cache`/`block_cache_sync_all` aren't defined
+    /// anywhere in this tree, only called from here, so there's no
+    /// struct in this file to add a dirty bit to.
+    pub fn fsync(&self) {
         block_cache_sync_all();
-        size
     }
 
+    /// A `sync_ordered` helper that flushes newly-allocated data blocks
+    /// before the metadata block referencing them -- so a crash between
+    /// the two never leaves an inode pointing at an unwritten block --
+    /// can't land from this file for the same reason [`Self::fsync`]'s
+    /// doc comment gives: it's `BlockCache`/`get_block_cache` that would
+    /// need the per-block flush-one-block-at-a-time primitive to build
+    /// `sync_ordered` out of, and neither is defined anywhere in this
+    /// tree. `write_at`/`write_at_nosync` above already do the right
+    /// thing in spirit -- `increase_size` (which wires in the new block
+    /// pointers) runs inside the same `modify_disk_inode` call that
+    /// writes the new data, before `block_cache_sync_all()` is ever
+    /// reached -- but `block_cache_sync_all()` flushes the *entire*
+    /// cache in one undifferentiated pass, with no ordering guarantee
+    /// between any two of the blocks it flushes.
+
+    /// An `EasyFileSystem::fsck(&self) -> Vec<FsckError>` that walks every
+    /// inode reachable from the root, marks the blocks it finds
+    /// referenced, and diffs that against the allocation bitmaps for
+    /// leaked or double-allocated blocks can't land in this crate at
+    /// all: `EasyFileSystem` has no `struct`/`impl` anywhere in this
+    /// tree (see the `increase_size` doc comment above), only ever
+    /// referenced as a bare `Arc<Mutex<EasyFileSystem>>`/`MutexGuard`
+    /// parameter type -- and the inode/data-block bitmaps `fsck` would
+    /// need to scan live on it, not on `Inode`. The "walk every inode
+    /// reachable from the root" half could in principle be written from
+    /// here via `find_by_id`/`ls`, but without the bitmaps to compare
+    /// against, a walk alone can't tell "leaked" from "never allocated".
+
+    /// True LRU eviction with a configurable capacity constant for the
+    /// block cache -- replacing whatever fixed-size-with-some-eviction
+    /// policy `get_block_cache` currently uses, and tracking access order
+    /// on every hit -- can't land from this file either, and for the same
+    /// root cause as the two notes above: `BlockCache`/`get_block_cache`
+    /// have no `struct`/`fn` anywhere in this tree, only called from
+    /// here (and from `DiskInode::get_block_id` in `crate::layout`).
+    /// There's no access-order field, no capacity constant, and no
+    /// eviction loop in this crate to change -- whoever defines
+    /// `get_block_cache` owns all three.
+
+    /// A `read_block_prio(block_id, buf, prio)` on the block device
+    /// abstraction, threaded from an opt-in `Inode::read_at` variant so
+    /// an interactive read can jump ahead of bulk prefetch, can't land
+    /// in this crate for the same root cause as the three notes above:
+    /// `BlockDevice` is only ever referenced here as a bare `dyn
+    /// BlockDevice` trait-object field (`self.block_device`), never
+    /// defined -- there's no trait declaration in this tree to add a
+    /// `read_block_prio` method to, and no internal queue on the other
+    /// side of `read_block`/`write_block` for a priority to jump ahead
+    /// in even if there were. `get_block_cache`'s absence (see the LRU
+    /// note just above) closes off the other angle too: a priority hint
+    /// has nothing to matter to once it clears the cache's lookup and
+    /// reaches the device, since there's no readahead/async queuing
+    /// layer in this tree for "jump ahead of" to mean anything against
+    /// in the first place -- the request's own "once readahead/async
+    /// queuing exist" caveat is exactly this tree's current state.
+
     /// Write data to current inode
     pub fn write_at_locked(&self, offset: usize, buf: &[u8], fs: &mut MutexGuard<EasyFileSystem>) -> usize {
         let size = self.modify_disk_inode(|disk_inode| {
             self.increase_size((offset + buf.len()) as u32, disk_inode, fs);
-            disk_inode.write_at(offset, buf, &self.block_device)
+            let size = disk_inode.write_at(offset, buf, &self.block_device);
+            disk_inode.mtime = next_tick();
+            size
         });
         block_cache_sync_all();
         size
     }
 
+    /// Resize this inode's content to exactly `new_size`, the way
+    /// `truncate`/`ftruncate` do: shrinking frees the now-unused tail via
+    /// [`Inode::decrease_size`], growing allocates (zeroed, like a fresh
+    /// block) data blocks via [`Inode::increase_size`] without writing
+    /// anything into them.
+    pub fn truncate(&self, new_size: u32) -> isize {
+        let mut fs = self.fs.lock();
+        self.modify_disk_inode(|disk_inode| {
+            if new_size < disk_inode.size {
+                self.decrease_size(new_size, disk_inode, &mut fs);
+            } else if new_size > disk_inode.size {
+                self.increase_size(new_size, disk_inode, &mut fs);
+            }
+            disk_inode.mtime = next_tick();
+        });
+        block_cache_sync_all();
+        0
+    }
+
+    /// Preallocate space covering `[offset, offset+len)` without writing
+    /// any data into it, `fallocate(2)`-style -- wires in the same
+    /// zeroed-on-allocation data blocks [`Inode::truncate`]'s grow path
+    /// already uses, a no-op if `offset + len` doesn't reach past the
+    /// current size. Bounded by [`DiskInode::max_size`] the same way
+    /// [`Inode::write_at`] is: preallocating past the inode's addressing
+    /// capacity is exactly as impossible as writing past it.
+    pub fn fallocate(&self, offset: usize, len: usize) -> isize {
+        let target = offset.saturating_add(len).min(DiskInode::max_size() as usize) as u32;
+        let mut fs = self.fs.lock();
+        self.modify_disk_inode(|disk_inode| {
+            if target > disk_inode.size {
+                self.increase_size(target, disk_inode, &mut fs);
+                disk_inode.mtime = next_tick();
+            }
+        });
+        block_cache_sync_all();
+        0
+    }
+
     /// Clear the data in current inode
     pub fn clear(&self) {
         let mut fs = self.fs.lock();
@@ -498,3 +1375,32 @@ impl Inode {
         block_cache_sync_all();
     }
 }
+
+/// Lazy, one-chunk-at-a-time iterator over a directory's dentries,
+/// returned by [`Inode::iter_dir`]
+pub struct DirEntryIter<'a> {
+    _fs: MutexGuard<'a, EasyFileSystem>,
+    inode: &'a Inode,
+    cursor: usize,
+    file_count: usize,
+}
+
+impl<'a> Iterator for DirEntryIter<'a> {
+    type Item = (DirEntry, u32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cursor >= self.file_count {
+            return None;
+        }
+        let idx = self.cursor;
+        let mut dirent = DirEntry::empty();
+        let read = self.inode.read_disk_inode(|disk_inode| {
+            disk_inode.read_at(idx * DIRENT_SZ, dirent.as_bytes_mut(), &self.inode.block_device)
+        });
+        if read != DIRENT_SZ {
+            return None;
+        }
+        self.cursor += 1;
+        Some((dirent, idx as u32))
+    }
+}
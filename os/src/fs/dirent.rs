@@ -0,0 +1,49 @@
+//! The `getdents64` directory-entry record format handed back to userspace
+
+use alloc::vec::Vec;
+
+/// Unknown file type, used when the caller couldn't determine one
+pub const DT_UNKNOWN: u8 = 0;
+/// Directory
+pub const DT_DIR: u8 = 4;
+/// Regular file
+pub const DT_REG: u8 = 8;
+/// Symbolic link
+pub const DT_LNK: u8 = 10;
+
+/// Fixed header size of a `linux_dirent64` record, ahead of its
+/// nul-terminated `d_name`: `d_ino` (8) + `d_off` (8) + `d_reclen` (2) +
+/// `d_type` (1)
+const HEADER_LEN: usize = 19;
+
+/// Append one `linux_dirent64`-shaped record (fixed header, then
+/// `d_name`, nul-terminated and padded to 8-byte alignment) to `out`.
+///
+/// Returns the record's length, or `None` without touching `out` if it
+/// wouldn't fit in the `limit` bytes the caller has left in its buffer --
+/// the same "stop before the partial entry" contract `getdents64` callers
+/// expect, so a loop can just retry next call for whatever didn't fit.
+pub fn encode_dirent64(
+    ino: u64,
+    next_off: i64,
+    d_type: u8,
+    name: &str,
+    limit: usize,
+    out: &mut Vec<u8>,
+) -> Option<usize> {
+    let name_bytes = name.as_bytes();
+    let unaligned = HEADER_LEN + name_bytes.len() + 1;
+    let reclen = (unaligned + 7) & !7;
+    if reclen > limit {
+        return None;
+    }
+    out.extend_from_slice(&ino.to_ne_bytes());
+    out.extend_from_slice(&next_off.to_ne_bytes());
+    out.extend_from_slice(&(reclen as u16).to_ne_bytes());
+    out.push(d_type);
+    out.extend_from_slice(name_bytes);
+    // Zero-fill the rest of the record: the first such byte is d_name's
+    // nul terminator, the remainder is alignment padding.
+    out.resize(out.len() + (reclen - (HEADER_LEN + name_bytes.len())), 0);
+    Some(reclen)
+}
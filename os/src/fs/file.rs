@@ -0,0 +1,62 @@
+//! The `File` trait shared by every kind of file descriptor
+
+use alloc::sync::Arc;
+use easy_fs::Inode;
+
+use crate::mm::UserBuffer;
+
+/// Returned by [`File::read`]/[`File::write`] instead of blocking, when
+/// the file is in non-blocking mode and the call would otherwise have
+/// had to wait (an empty pipe read, a full pipe write)
+pub const EAGAIN: isize = -11;
+
+/// A byte-stream or filesystem object reachable through a task's fd table.
+/// Implemented by both filesystem-backed files and character devices such
+/// as stdin/stdout.
+pub trait File: Send + Sync {
+    /// Whether this file can be read from
+    fn readable(&self) -> bool;
+    /// Whether this file can be written to
+    fn writable(&self) -> bool;
+    /// Read into `buf`, returning the number of bytes read, or
+    /// [`EAGAIN`] if the file is non-blocking and the read would
+    /// otherwise have to wait
+    fn read(&self, buf: UserBuffer) -> isize;
+    /// Write out `buf`, returning the number of bytes written, or
+    /// [`EAGAIN`] if the file is non-blocking and the write would
+    /// otherwise have to wait
+    fn write(&self, buf: UserBuffer) -> isize;
+    /// The filesystem inode backing this file, if any (character devices
+    /// like stdin/stdout have none)
+    fn inode(&self) -> Option<Arc<Inode>>;
+    /// Reposition this file's read/write cursor, POSIX-`lseek`-style.
+    /// Returns the resulting absolute offset, or `None` if this file isn't
+    /// seekable (e.g. stdin/stdout). Defaults to "not seekable" so
+    /// implementors that have no cursor to move don't need to override it.
+    fn lseek(&self, _offset: isize, _whence: usize) -> Option<usize> {
+        None
+    }
+    /// Put this file into (or out of) non-blocking mode, e.g. for
+    /// `O_NONBLOCK`/`F_SETFL`. Defaults to a no-op for files that never
+    /// block in the first place (regular files).
+    fn set_nonblocking(&self, _nonblocking: bool) {}
+    /// Whether this file is currently in non-blocking mode. Defaults to
+    /// `false` for files that don't override [`File::set_nonblocking`].
+    fn nonblocking(&self) -> bool {
+        false
+    }
+    /// Whether a [`File::read`] right now would return data immediately
+    /// instead of blocking, without actually consuming anything -- the
+    /// check `sys_poll` needs for `POLLIN`. Defaults to `true`: a plain
+    /// file has no buffer to run dry, so it's always immediately
+    /// readable.
+    fn ready_to_read(&self) -> bool {
+        true
+    }
+    /// Same as [`File::ready_to_read`], for `POLLOUT`: whether a
+    /// [`File::write`] right now would accept data immediately instead
+    /// of blocking. Defaults to `true` for the same reason.
+    fn ready_to_write(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,233 @@
+//! Anonymous pipes: an in-memory ring buffer shared by a read end and a
+//! write end, each reachable through a task's fd table as a [`File`]
+
+use alloc::sync::{Arc, Weak};
+
+use crate::mm::UserBuffer;
+use crate::sync::UPSafeCell;
+use crate::task::suspend_current_and_run_next;
+
+use super::{File, EAGAIN};
+use easy_fs::Inode;
+
+const RING_BUFFER_SIZE: usize = 32;
+
+#[derive(Copy, Clone, PartialEq)]
+enum RingBufferStatus {
+    Full,
+    Empty,
+    Normal,
+}
+
+/// The ring buffer shared by a pipe's two ends
+pub struct PipeRingBuffer {
+    arr: [u8; RING_BUFFER_SIZE],
+    head: usize,
+    tail: usize,
+    status: RingBufferStatus,
+    /// The write end, kept as a `Weak` so the buffer doesn't itself keep
+    /// the write end alive -- once every strong `Arc<Pipe>` to the write
+    /// end is dropped, `write_end_closed` reports EOF to the reader.
+    write_end: Option<Weak<Pipe>>,
+}
+
+impl PipeRingBuffer {
+    /// Create an empty ring buffer with no write end registered yet
+    pub fn new() -> Self {
+        Self {
+            arr: [0; RING_BUFFER_SIZE],
+            head: 0,
+            tail: 0,
+            status: RingBufferStatus::Empty,
+            write_end: None,
+        }
+    }
+
+    /// Remember `write_end` so [`PipeRingBuffer::all_write_ends_closed`]
+    /// can later tell whether the writer side is still reachable
+    pub fn set_write_end(&mut self, write_end: &Arc<Pipe>) {
+        self.write_end = Some(Arc::downgrade(write_end));
+    }
+
+    /// Push one byte, advancing `tail`. Caller must have already checked
+    /// the buffer isn't full.
+    pub fn write_byte(&mut self, byte: u8) {
+        self.status = RingBufferStatus::Normal;
+        self.arr[self.tail] = byte;
+        self.tail = (self.tail + 1) % RING_BUFFER_SIZE;
+        if self.tail == self.head {
+            self.status = RingBufferStatus::Full;
+        }
+    }
+
+    /// Pop one byte, advancing `head`. Caller must have already checked
+    /// the buffer isn't empty.
+    pub fn read_byte(&mut self) -> u8 {
+        self.status = RingBufferStatus::Normal;
+        let byte = self.arr[self.head];
+        self.head = (self.head + 1) % RING_BUFFER_SIZE;
+        if self.head == self.tail {
+            self.status = RingBufferStatus::Empty;
+        }
+        byte
+    }
+
+    /// How many bytes are available to read right now
+    pub fn available_read(&self) -> usize {
+        if self.status == RingBufferStatus::Empty {
+            0
+        } else if self.tail > self.head {
+            self.tail - self.head
+        } else {
+            self.tail + RING_BUFFER_SIZE - self.head
+        }
+    }
+
+    /// How much free space is available to write right now
+    pub fn available_write(&self) -> usize {
+        if self.status == RingBufferStatus::Full {
+            0
+        } else {
+            RING_BUFFER_SIZE - self.available_read()
+        }
+    }
+
+    /// Whether the write end has no remaining strong references, i.e. the
+    /// writer side has exited/closed and no more bytes will ever arrive
+    pub fn all_write_ends_closed(&self) -> bool {
+        self.write_end.as_ref().unwrap().upgrade().is_none()
+    }
+}
+
+/// One end of a pipe: readable xor writable, sharing a [`PipeRingBuffer`]
+/// with its sibling end
+pub struct Pipe {
+    readable: bool,
+    writable: bool,
+    buffer: Arc<UPSafeCell<PipeRingBuffer>>,
+    /// Set via [`File::set_nonblocking`] (e.g. `O_NONBLOCK`/`F_SETFL`):
+    /// whether [`Pipe::read`]/[`Pipe::write`] return [`EAGAIN`] instead of
+    /// suspending the caller on a would-block empty read or full write
+    nonblocking: UPSafeCell<bool>,
+}
+
+impl Pipe {
+    /// Wrap `buffer` as the read end
+    pub fn read_end_with_buffer(buffer: Arc<UPSafeCell<PipeRingBuffer>>) -> Self {
+        Self {
+            readable: true,
+            writable: false,
+            buffer,
+            nonblocking: unsafe { UPSafeCell::new(false) },
+        }
+    }
+
+    /// Wrap `buffer` as the write end
+    pub fn write_end_with_buffer(buffer: Arc<UPSafeCell<PipeRingBuffer>>) -> Self {
+        Self {
+            readable: false,
+            writable: true,
+            buffer,
+            nonblocking: unsafe { UPSafeCell::new(false) },
+        }
+    }
+}
+
+/// Create a fresh pipe, returning `(read_end, write_end)`
+pub fn make_pipe() -> (Arc<Pipe>, Arc<Pipe>) {
+    let buffer = Arc::new(unsafe { UPSafeCell::new(PipeRingBuffer::new()) });
+    let read_end = Arc::new(Pipe::read_end_with_buffer(buffer.clone()));
+    let write_end = Arc::new(Pipe::write_end_with_buffer(buffer.clone()));
+    buffer.exclusive_access().set_write_end(&write_end);
+    (read_end, write_end)
+}
+
+impl File for Pipe {
+    fn readable(&self) -> bool {
+        self.readable
+    }
+
+    fn writable(&self) -> bool {
+        self.writable
+    }
+
+    fn read(&self, buf: UserBuffer) -> isize {
+        assert!(self.readable);
+        let mut read_size = 0usize;
+        let mut iter = buf.into_iter();
+        loop {
+            let mut ring_buffer = self.buffer.exclusive_access();
+            let available = ring_buffer.available_read();
+            if available == 0 {
+                if ring_buffer.all_write_ends_closed() {
+                    return read_size as isize;
+                }
+                if *self.nonblocking.exclusive_access() {
+                    return EAGAIN;
+                }
+                drop(ring_buffer);
+                suspend_current_and_run_next();
+                continue;
+            }
+            for _ in 0..available {
+                if let Some(byte_ref) = iter.next() {
+                    unsafe {
+                        *byte_ref = ring_buffer.read_byte();
+                    }
+                    read_size += 1;
+                } else {
+                    return read_size as isize;
+                }
+            }
+            return read_size as isize;
+        }
+    }
+
+    fn write(&self, buf: UserBuffer) -> isize {
+        assert!(self.writable);
+        let mut write_size = 0usize;
+        let mut iter = buf.into_iter();
+        loop {
+            let mut ring_buffer = self.buffer.exclusive_access();
+            let available = ring_buffer.available_write();
+            if available == 0 {
+                if *self.nonblocking.exclusive_access() {
+                    return EAGAIN;
+                }
+                drop(ring_buffer);
+                suspend_current_and_run_next();
+                continue;
+            }
+            for _ in 0..available {
+                if let Some(byte_ref) = iter.next() {
+                    ring_buffer.write_byte(unsafe { *byte_ref });
+                    write_size += 1;
+                } else {
+                    return write_size as isize;
+                }
+            }
+            return write_size as isize;
+        }
+    }
+
+    fn inode(&self) -> Option<Arc<Inode>> {
+        None
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) {
+        *self.nonblocking.exclusive_access() = nonblocking;
+    }
+
+    fn nonblocking(&self) -> bool {
+        *self.nonblocking.exclusive_access()
+    }
+
+    fn ready_to_read(&self) -> bool {
+        let ring_buffer = self.buffer.exclusive_access();
+        ring_buffer.available_read() > 0 || ring_buffer.all_write_ends_closed()
+    }
+
+    fn ready_to_write(&self) -> bool {
+        self.buffer.exclusive_access().available_write() > 0
+    }
+}
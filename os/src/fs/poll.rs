@@ -0,0 +1,24 @@
+//! The `pollfd` array passed to `sys_poll`
+
+/// Requested/returned event: the fd has data ready to read without
+/// blocking
+pub const POLLIN: u16 = 0x0001;
+/// Requested/returned event: the fd can be written to without blocking
+pub const POLLOUT: u16 = 0x0004;
+/// Returned event: `fd` didn't name an open file at all. Set
+/// unconditionally, regardless of what was requested in `events`.
+pub const POLLNVAL: u16 = 0x0020;
+
+/// One entry in the array `sys_poll` scans: which fd to watch, which
+/// events the caller cares about, and which of those actually fired.
+/// Laid out to match the userspace `pollfd` ABI.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PollFd {
+    /// The fd to watch
+    pub fd: i32,
+    /// Events the caller is interested in (`POLLIN`/`POLLOUT`)
+    pub events: u16,
+    /// Events that actually fired, filled in by `sys_poll`
+    pub revents: u16,
+}
@@ -0,0 +1,99 @@
+//! A synthetic, read-only `File` whose contents are generated by a
+//! function instead of read from disk blocks -- the building block for
+//! `/proc`-style nodes like `/proc/self/status`.
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use easy_fs::Inode;
+
+use crate::mm::UserBuffer;
+use crate::sync::UPSafeCell;
+
+use super::File;
+
+/// A read-only file backed by `generate` rather than an inode. The first
+/// `read` after open calls `generate` once and caches the result, so
+/// several short reads of the same open still see one consistent
+/// snapshot instead of a fresh one per call; opening the node again (a
+/// fresh `ProcFile`) is what "regenerate on each open" means here.
+pub struct ProcFile {
+    contents: UPSafeCell<Option<String>>,
+    generate: fn() -> String,
+}
+
+impl ProcFile {
+    /// Wrap `generate` as a fresh, not-yet-materialized synthetic file
+    pub fn new(generate: fn() -> String) -> Self {
+        Self {
+            contents: unsafe { UPSafeCell::new(None) },
+            generate,
+        }
+    }
+}
+
+impl File for ProcFile {
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        false
+    }
+
+    fn read(&self, mut buf: UserBuffer) -> isize {
+        let mut contents = self.contents.exclusive_access();
+        if contents.is_none() {
+            *contents = Some((self.generate)());
+        }
+        let data = contents.as_ref().unwrap().as_bytes();
+        let mut copied = 0usize;
+        for slot in buf.into_iter() {
+            if copied >= data.len() {
+                break;
+            }
+            unsafe {
+                *slot = data[copied];
+            }
+            copied += 1;
+        }
+        copied as isize
+    }
+
+    /// Read-only: there's nothing on the other end of the generator for
+    /// a write to affect.
+    fn write(&self, _buf: UserBuffer) -> isize {
+        -1
+    }
+
+    fn inode(&self) -> Option<Arc<Inode>> {
+        None
+    }
+}
+
+/// `/proc/self/status`'s generator: the current task's status and pid as
+/// text. Syscall counts (the third thing the real `/proc/self/status`
+/// idea called for) aren't included -- they'd come from
+/// `TaskControlBlock::get_taskinfo`, which is only ever called (from
+/// `sys_task_info` in `crate::syscall::process`), never defined in this
+/// tree, and `TaskControlBlockInner` itself has no `syscall_times` field
+/// to read them from directly either.
+///
+/// Wiring a path like `/proc/self/status` through to a fresh
+/// `ProcFile::new(self_status)` is `open_file`'s job, and `open_file`
+/// isn't in this tree either -- only ever called, from `sys_open` in
+/// `crate::syscall::fs` and `sys_spawn`/`sys_exec` in
+/// `crate::syscall::process`. There's no
+/// dispatch point reachable from this file to add the synthetic path to.
+pub fn self_status() -> String {
+    use crate::task::TaskStatus;
+    let task = crate::task::current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    let state = match inner.task_status {
+        TaskStatus::UnInit => "UnInit",
+        TaskStatus::Ready => "Ready",
+        TaskStatus::Running => "Running",
+        TaskStatus::Exited => "Exited",
+        TaskStatus::Traced => "Traced",
+    };
+    alloc::format!("Pid:\t{}\nState:\t{}\n", task.pid.0, state)
+}
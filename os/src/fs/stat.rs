@@ -0,0 +1,29 @@
+//! The `fstat` return type handed back to userspace
+
+use super::StatMode;
+
+/// File metadata as reported by `sys_fstat`, laid out to match the
+/// userspace `Stat` ABI: fixed-width fields plus trailing padding so the
+/// struct's size can grow without breaking existing callers.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct Stat {
+    /// ID of device containing file
+    pub dev: u64,
+    /// inode number
+    pub ino: u64,
+    /// file type and mode
+    pub mode: StatMode,
+    /// number of hard links
+    pub nlink: u32,
+    /// size of the file's content, in bytes
+    pub size: u64,
+    /// last access time
+    pub atime: u64,
+    /// last content modification time
+    pub mtime: u64,
+    /// last metadata change time
+    pub ctime: u64,
+    /// reserved for future expansion
+    pub pad: [u64; 3],
+}
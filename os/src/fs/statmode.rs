@@ -0,0 +1,30 @@
+//! The kernel-side `st_mode` bits reported by `sys_fstat`/`sys_stat`
+
+use easy_fs::StatMode as VfsStatMode;
+
+bitflags! {
+    /// Mirrors [`easy_fs::StatMode`]'s bit layout, so a [`super::Stat`]
+    /// handed back to userspace carries the same `st_mode` bits a real
+    /// `stat(2)` would report for a directory/file/symlink.
+    pub struct StatMode: u32 {
+        /// null
+        const NULL  = 0;
+        /// directory
+        const DIR   = 0o040000;
+        /// ordinary regular file
+        const FILE  = 0o100000;
+        /// symbolic link
+        const LINK  = 0o120000;
+    }
+}
+
+impl From<VfsStatMode> for StatMode {
+    fn from(mode: VfsStatMode) -> Self {
+        match mode {
+            VfsStatMode::DIR => StatMode::DIR,
+            VfsStatMode::FILE => StatMode::FILE,
+            VfsStatMode::LINK => StatMode::LINK,
+            _ => StatMode::NULL,
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! A bounded-buffer MPMC channel, the classic producer/consumer pattern
+//! packaged so a syscall (or a kernel thread) doesn't have to re-derive
+//! it from a raw [`Mutex`]/[`Semaphore`] pair every time it needs one.
+//! `empty` counts free slots and `full` counts filled ones; blocking on
+//! either goes through the same `resmon` accounting as any other
+//! `SyncRes`, so the deadlock detector sees a producer waiting on `empty`
+//! or a consumer waiting on `full` exactly like it would a `Mutex`.
+
+use super::mutex::{Mutex, MutexBlocking};
+use super::semaphore::Semaphore;
+use super::UPSafeCell;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+
+/// A fixed-capacity FIFO shared between producers and consumers. Sending
+/// into a full channel blocks until a consumer makes room; receiving
+/// from an empty one blocks until a producer fills it.
+pub struct Channel<T> {
+    mutex: Arc<MutexBlocking>,
+    empty: Arc<Semaphore>,
+    full: Arc<Semaphore>,
+    queue: UPSafeCell<VecDeque<T>>,
+}
+
+impl<T> Channel<T> {
+    /// Create a channel holding at most `capacity` items in flight at
+    /// once. `capacity` must be at least 1 -- a zero-capacity channel
+    /// would start `empty` at 0 and every `send` would block forever.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            mutex: Arc::new(MutexBlocking::new()),
+            empty: Arc::new(Semaphore::new(capacity)),
+            full: Arc::new(Semaphore::new(0)),
+            queue: unsafe { UPSafeCell::new(VecDeque::new()) },
+        }
+    }
+
+    /// Block until a slot is free, then enqueue `item`.
+    pub fn send(&self, item: T) {
+        self.empty.down();
+        self.mutex.lock();
+        self.queue.exclusive_access().push_back(item);
+        self.mutex.unlock();
+        self.full.up();
+    }
+
+    /// Block until an item is available, then dequeue and return it.
+    pub fn recv(&self) -> T {
+        self.full.down();
+        self.mutex.lock();
+        // `full.down()` already accounted for exactly one enqueued item,
+        // so the queue can't be empty here even though nothing stops
+        // another consumer from having raced in between.
+        let item = self.queue.exclusive_access().pop_front().unwrap();
+        self.mutex.unlock();
+        self.empty.up();
+        item
+    }
+}
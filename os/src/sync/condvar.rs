@@ -0,0 +1,138 @@
+//! Condvar
+
+use crate::sync::UPSafeCell;
+use crate::sync::{Mutex, SyncRes, DEAD_LOCK};
+use crate::sync::timeout::{deadline_after, TimeoutWaitable, TIMEOUT_QUEUE, TIMED_OUT};
+use crate::task::{
+    block_current_and_run_next, current_process, current_task, wakeup_task, TaskControlBlock,
+};
+use alloc::{collections::VecDeque, sync::Arc};
+
+/// Condvar structure
+pub struct Condvar {
+    /// condvar inner
+    pub inner: UPSafeCell<CondvarInner>,
+    resid: u32,
+}
+
+pub struct CondvarInner {
+    pub wait_queue: VecDeque<Arc<TaskControlBlock>>,
+}
+
+impl Condvar {
+    /// Create a new condvar
+    pub fn new() -> Self {
+        #[cfg(feature = "debug_sem")]
+        trace!("kernel: Condvar::new");
+        let curproc = current_process();
+        let mut resmon = curproc.resmon.exclusive_access();
+        Self {
+            inner: unsafe {
+                UPSafeCell::new(CondvarInner {
+                    wait_queue: VecDeque::new(),
+                })
+            },
+            resid: resmon.create_res(1),
+        }
+    }
+
+    /// wake up one task waiting on this condvar
+    pub fn signal(&self) {
+        #[cfg(feature = "debug_sem")]
+        trace!("kernel: Condvar::signal");
+        let mut inner = self.inner.exclusive_access();
+        if let Some(task) = inner.wait_queue.pop_front() {
+            wakeup_task(task);
+        }
+    }
+
+    /// wake up every task waiting on this condvar
+    pub fn broadcast(&self) {
+        #[cfg(feature = "debug_sem")]
+        trace!("kernel: Condvar::broadcast");
+        let mut inner = self.inner.exclusive_access();
+        while let Some(task) = inner.wait_queue.pop_front() {
+            wakeup_task(task);
+        }
+    }
+
+    /// atomically release `mutex` and block the current task on this
+    /// condvar, re-acquiring `mutex` once woken up
+    pub fn wait(&self, mutex: Arc<dyn Mutex>) -> i32 {
+        #[cfg(feature = "debug_sem")]
+        trace!("kernel: Condvar::wait");
+        let mut inner = self.inner.exclusive_access();
+        inner.wait_queue.push_back(current_task().unwrap());
+        drop(inner);
+        self.need();
+        if let Some(_) = self.check() {
+            return DEAD_LOCK;
+        }
+        mutex.unlock();
+        block_current_and_run_next();
+        mutex.lock();
+        // Granted and immediately given back: a condvar wait doesn't hold
+        // onto anything past this point, unlike a mutex held until unlock,
+        // so acquire/release bracket the wait the same way lock/unlock do.
+        self.acquire();
+        self.release();
+        0
+    }
+
+    /// Like [`Condvar::wait`], but gives up and returns [`TIMED_OUT`] if
+    /// `ticks` pass before `signal`/`broadcast` wakes the waiter.
+    /// `mutex` is re-acquired before returning either way, matching
+    /// `wait`'s "released for the duration of the wait" contract.
+    pub fn wait_timeout(self: &Arc<Self>, mutex: Arc<dyn Mutex>, ticks: usize) -> i32 {
+        #[cfg(feature = "debug_sem")]
+        trace!("kernel: Condvar::wait_timeout");
+        let mut inner = self.inner.exclusive_access();
+        let task = current_task().unwrap();
+        inner.wait_queue.push_back(task.clone());
+        drop(inner);
+        self.need();
+        if let Some(_) = self.check() {
+            return DEAD_LOCK;
+        }
+        mutex.unlock();
+        let deadline = deadline_after(ticks);
+        TIMEOUT_QUEUE.register(deadline, task.clone(), self.clone() as Arc<dyn TimeoutWaitable>);
+        block_current_and_run_next();
+        mutex.lock();
+        self.acquire();
+        self.release();
+        if TIMEOUT_QUEUE.take_timed_out(&task) {
+            return TIMED_OUT;
+        }
+        0
+    }
+}
+
+impl SyncRes for Condvar {
+    fn getid(&self) -> u32 {
+        self.resid
+    }
+}
+
+impl Drop for Condvar {
+    /// Hand `resid` back to [`super::resmon::ResMonitor`]'s free list, the
+    /// same as [`super::mutex::MutexSpin`]'s impl, so a later
+    /// `Condvar::new` in the same process can reuse it.
+    fn drop(&mut self) {
+        let curproc = current_process();
+        curproc.resmon.exclusive_access().free_res(self.resid);
+    }
+}
+
+impl TimeoutWaitable for Condvar {
+    fn cancel_wait(&self, task: &Arc<TaskControlBlock>) -> bool {
+        let mut inner = self.inner.exclusive_access();
+        if let Some(pos) = inner.wait_queue.iter().position(|t| Arc::ptr_eq(t, task)) {
+            inner.wait_queue.remove(pos);
+            self.unneed();
+            true
+        } else {
+            false
+        }
+    }
+}
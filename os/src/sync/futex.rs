@@ -0,0 +1,68 @@
+//! A minimal `FUTEX_WAIT`/`FUTEX_WAKE` wait queue, keyed by the futex
+//! word's physical address rather than a handle the caller has to create
+//! up front -- unlike [`super::Mutex`]/[`super::Semaphore`], two
+//! unrelated tasks that happen to `mmap` the same page find each other
+//! by address alone.
+
+use super::UPSafeCell;
+use crate::task::{block_current_and_run_next, current_task, wakeup_task, TaskControlBlock};
+use alloc::{collections::BTreeMap, collections::VecDeque, sync::Arc};
+use lazy_static::lazy_static;
+
+struct FutexTable {
+    queues: BTreeMap<usize, VecDeque<Arc<TaskControlBlock>>>,
+}
+
+lazy_static! {
+    /// Every futex word currently being waited on, keyed by its physical
+    /// address. An address with no waiters has no entry at all, so a
+    /// `FUTEX_WAKE` on a never-contended word is just a map lookup miss.
+    static ref FUTEX_TABLE: UPSafeCell<FutexTable> = unsafe {
+        UPSafeCell::new(FutexTable {
+            queues: BTreeMap::new(),
+        })
+    };
+}
+
+/// Park the calling task on `key` (the futex word's physical address).
+/// The caller is responsible for having already checked the word's
+/// value still matches what it expects to wait for -- by the time this
+/// returns control to the task, it must re-check, since a `futex_wake`
+/// racing with the check-and-park isn't excluded here any more tightly
+/// than real `FUTEX_WAIT` excludes it.
+pub fn wait(key: usize) {
+    let task = current_task().unwrap();
+    FUTEX_TABLE
+        .exclusive_access()
+        .queues
+        .entry(key)
+        .or_insert_with(VecDeque::new)
+        .push_back(task);
+    block_current_and_run_next();
+}
+
+/// Wake up to `max_wake` tasks parked on `key`, in park order. Returns
+/// how many were actually woken.
+pub fn wake(key: usize, max_wake: usize) -> usize {
+    let mut table = FUTEX_TABLE.exclusive_access();
+    let woken = match table.queues.get_mut(&key) {
+        Some(queue) => {
+            let mut woken = 0;
+            while woken < max_wake {
+                match queue.pop_front() {
+                    Some(task) => {
+                        wakeup_task(task);
+                        woken += 1;
+                    }
+                    None => break,
+                }
+            }
+            woken
+        }
+        None => 0,
+    };
+    if table.queues.get(&key).map_or(false, VecDeque::is_empty) {
+        table.queues.remove(&key);
+    }
+    woken
+}
@@ -1,4 +1,16 @@
 //! Mutex (spin-like and blocking(sleep))
+//!
+//! A debug-only reentrant-borrow guard on [`UPSafeCell::exclusive_access`]
+//! -- panicking with a clear message instead of deadlocking or aliasing
+//! when a method re-enters while a guard from the same cell is still
+//! live -- can't land from this file, or from any other file in this
+//! tree: `UPSafeCell` has no `struct`/`impl` anywhere here, only ever
+//! referenced (as it is a few lines below, via `UPSafeCell::new` and
+//! every `.exclusive_access()` call in this module and every other
+//! `sync` file). A borrow flag needs somewhere to live -- a `Cell<bool>`
+//! or similar alongside whatever `RefCell`-like mechanism
+//! `exclusive_access` already uses internally -- and there's no struct
+//! definition in this tree to add one to.
 
 use super::UPSafeCell;
 use crate::task::TaskControlBlock;
@@ -6,6 +18,7 @@ use crate::task::{block_current_and_run_next, suspend_current_and_run_next};
 use crate::task::{current_process, current_task, wakeup_task};
 use alloc::{collections::VecDeque, sync::Arc};
 use crate::sync::{ SyncRes, DEAD_LOCK };
+use crate::sync::timeout::{deadline_after, TimeoutWaitable, TIMEOUT_QUEUE, TIMED_OUT};
 
 /// Mutex trait
 pub trait Mutex: Sync + Send + SyncRes {
@@ -22,6 +35,16 @@ pub struct MutexSpin {
 }
 
 impl MutexSpin {
+    /// Spin iterations a contended lock tolerates before paying for a
+    /// deadlock [`SyncRes::check`]. Checking on every spin re-derives the
+    /// same answer off a need matrix that hasn't changed, for the cost of
+    /// walking it again; it also means a lock held only briefly can get
+    /// unlucky and have `check()` run mid-hold and call it a deadlock,
+    /// even though the holder was always about to unlock. Spinning this
+    /// many times first gives a short-held lock a chance to release
+    /// before the detector ever looks.
+    const DEADLOCK_CHECK_SPINS: usize = 1000;
+
     /// Create a new spinlock mutex
     pub fn new() -> Self {
         let curproc = current_process();
@@ -39,23 +62,49 @@ impl SyncRes for MutexSpin {
     }
 }
 
+impl Drop for MutexSpin {
+    /// Hand `resid` back to [`super::resmon::ResMonitor`]'s free list so
+    /// a later `MutexSpin::new`/`MutexBlocking::new`/etc. in the same
+    /// process can reuse it instead of growing the deadlock matrices
+    /// forever.
+    fn drop(&mut self) {
+        let curproc = current_process();
+        curproc.resmon.exclusive_access().free_res(self.resid);
+    }
+}
+
 impl Mutex for MutexSpin {
     /// Lock the spinlock mutex
     fn lock(&self) -> i32 {
         #[cfg(feature = "debug_mutx")]
         trace!("kernel: MutexSpin::lock");
+        // Recorded once, the first time this spin finds the lock held,
+        // not re-recorded on every iteration: `need()` is already
+        // idempotent once set, but calling it every spin still means
+        // re-acquiring resmon's lock for no new information.
+        let mut needed = false;
+        let mut spins = 0usize;
         loop {
             let mut locked = self.locked.exclusive_access();
             if *locked {
                 drop(locked);
-                self.need();
-                if let Some(_) = self.check() {
-                    return DEAD_LOCK;
+                if !needed {
+                    self.need();
+                    needed = true;
+                }
+                spins += 1;
+                if spins % Self::DEADLOCK_CHECK_SPINS == 0 {
+                    if let Some(_) = self.check() {
+                        return DEAD_LOCK;
+                    }
                 }
                 suspend_current_and_run_next();
                 continue;
             } else {
                 *locked = true;
+                // `acquire()` resets our pending `need` entry as part of
+                // charging the allocation, so there's nothing extra to
+                // clear here even though `needed` was set above.
                 self.acquire();
                 return 0;
             }
@@ -80,6 +129,15 @@ pub struct MutexBlocking {
 pub struct MutexBlockingInner {
     locked: bool,
     wait_queue: VecDeque<Arc<TaskControlBlock>>,
+    /// The task currently holding the lock, tracked so priority
+    /// inheritance has somewhere to donate to and unwind from
+    owner: Option<Arc<TaskControlBlock>>,
+    /// The owner's effective priority just before this mutex first
+    /// donated to it, i.e. what to restore on unlock. Recorded only once
+    /// per holding, on the first (lowest) donation, so a second,
+    /// still-held mutex's own donation to the same owner isn't clobbered
+    /// when this one unwinds.
+    pre_donation_priority: Option<usize>,
 }
 
 impl MutexBlocking {
@@ -94,6 +152,8 @@ impl MutexBlocking {
                 UPSafeCell::new(MutexBlockingInner {
                     locked: false,
                     wait_queue: VecDeque::new(),
+                    owner: None,
+                    pre_donation_priority: None,
                 })
             },
             resid: resmon.create_res(1), 
@@ -107,6 +167,14 @@ impl SyncRes for MutexBlocking {
     }
 }
 
+impl Drop for MutexBlocking {
+    /// See [`MutexSpin`]'s impl above.
+    fn drop(&mut self) {
+        let curproc = current_process();
+        curproc.resmon.exclusive_access().free_res(self.resid);
+    }
+}
+
 impl Mutex for MutexBlocking {
     /// lock the blocking mutex
     fn lock(&self) -> i32 {
@@ -114,15 +182,37 @@ impl Mutex for MutexBlocking {
         trace!("kernel: MutexBlocking::lock");
         let mut mutex_inner = self.inner.exclusive_access();
         if mutex_inner.locked {
-            mutex_inner.wait_queue.push_back(current_task().unwrap());
+            let waiter = current_task().unwrap();
+            // Priority inheritance: if we are more urgent than whoever is
+            // holding the lock, donate our priority to them so they get
+            // scheduled, run to completion, and unlock instead of being
+            // starved by a lower-priority task that never gets to run.
+            if let Some(owner) = mutex_inner.owner.as_ref() {
+                if waiter.priority() > owner.priority() {
+                    mutex_inner.pre_donation_priority.get_or_insert(owner.priority());
+                    owner.set_priority(waiter.priority());
+                }
+            }
+            mutex_inner.wait_queue.push_back(waiter.clone());
             drop(mutex_inner);
             self.need();
             if let Some(_) = self.check() {
+                // Bailing out without ever blocking: undo the push above,
+                // or a later `unlock` would pop this task off
+                // `wait_queue` and wake it even though it already
+                // returned (and likely moved on) instead of waiting.
+                let mut mutex_inner = self.inner.exclusive_access();
+                if let Some(pos) = mutex_inner.wait_queue.iter().position(|t| Arc::ptr_eq(t, &waiter)) {
+                    mutex_inner.wait_queue.remove(pos);
+                }
+                drop(mutex_inner);
+                self.unneed();
                 return DEAD_LOCK;
             }
             block_current_and_run_next();
         } else {
             mutex_inner.locked = true;
+            mutex_inner.owner = Some(current_task().unwrap());
             self.acquire();
         }
         0
@@ -134,11 +224,97 @@ impl Mutex for MutexBlocking {
         trace!("kernel: MutexBlocking::unlock");
         let mut mutex_inner = self.inner.exclusive_access();
         assert!(mutex_inner.locked);
-        if let Some(waking_task) = mutex_inner.wait_queue.pop_front() {
+        // The outgoing owner is done with the lock: unwind whatever this
+        // particular mutex donated to it, without touching any donation
+        // still owed by another mutex it continues to hold.
+        if let Some(owner) = mutex_inner.owner.take() {
+            if let Some(floor) = mutex_inner.pre_donation_priority.take() {
+                owner.set_priority(floor.max(owner.base_priority()));
+            }
+        }
+        // Hand off to the highest-priority waiter, not just whoever queued
+        // first -- a plain FIFO pop would let a low-priority waiter that
+        // arrived earlier keep a high-priority one (the very task priority
+        // inheritance exists to unblock) stuck behind it.
+        let next = mutex_inner
+            .wait_queue
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, t)| t.priority())
+            .map(|(idx, _)| idx);
+        if let Some(waking_task) = next.and_then(|idx| mutex_inner.wait_queue.remove(idx)) {
+            // Handing the lock straight to a waiter, not actually freeing
+            // it: release() then acquire_for() nets to no change in
+            // avail, but (unlike a bare release()) also moves the
+            // allocation onto the new owner and clears its pending
+            // `need`, the same way RwLock's handoff does. Skipping the
+            // acquire_for() would leave the deadlock detector thinking
+            // the resource is free while it's still held, and the
+            // waiter's stale `need` entry would linger indefinitely.
+            mutex_inner.owner = Some(waking_task.clone());
+            let tid = waking_task.get_tid().unwrap();
+            self.release();
+            self.acquire_for(tid);
             wakeup_task(waking_task);
         } else {
             mutex_inner.locked = false;
+            self.release();
+        }
+    }
+}
+
+impl MutexBlocking {
+    /// Like [`Mutex::lock`], but gives up and returns [`TIMED_OUT`] if
+    /// `ticks` pass before the mutex becomes available.
+    ///
+    /// This is inherent to `MutexBlocking` rather than a `Mutex` trait
+    /// method: `MutexSpin` never parks a task on a wait queue to begin
+    /// with, so there is nothing for a deadline to cancel it out of, and
+    /// registering it with `TIMEOUT_QUEUE` (which needs `Arc<dyn
+    /// TimeoutWaitable>`) would require `Mutex` itself to carry that
+    /// supertrait bound for a capability only one implementor has.
+    pub fn lock_timeout(self: &Arc<Self>, ticks: usize) -> i32 {
+        #[cfg(feature = "debug_mutx")]
+        trace!("kernel: MutexBlocking::lock_timeout");
+        let mut mutex_inner = self.inner.exclusive_access();
+        if mutex_inner.locked {
+            let waiter = current_task().unwrap();
+            if let Some(owner) = mutex_inner.owner.as_ref() {
+                if waiter.priority() > owner.priority() {
+                    mutex_inner.pre_donation_priority.get_or_insert(owner.priority());
+                    owner.set_priority(waiter.priority());
+                }
+            }
+            mutex_inner.wait_queue.push_back(waiter.clone());
+            drop(mutex_inner);
+            self.need();
+            if let Some(_) = self.check() {
+                return DEAD_LOCK;
+            }
+            let deadline = deadline_after(ticks);
+            TIMEOUT_QUEUE.register(deadline, waiter.clone(), self.clone() as Arc<dyn TimeoutWaitable>);
+            block_current_and_run_next();
+            if TIMEOUT_QUEUE.take_timed_out(&waiter) {
+                return TIMED_OUT;
+            }
+        } else {
+            mutex_inner.locked = true;
+            mutex_inner.owner = Some(current_task().unwrap());
+            self.acquire();
+        }
+        0
+    }
+}
+
+impl TimeoutWaitable for MutexBlocking {
+    fn cancel_wait(&self, task: &Arc<TaskControlBlock>) -> bool {
+        let mut inner = self.inner.exclusive_access();
+        if let Some(pos) = inner.wait_queue.iter().position(|t| Arc::ptr_eq(t, task)) {
+            inner.wait_queue.remove(pos);
+            self.unneed();
+            true
+        } else {
+            false
         }
-        self.release();
     }
 }
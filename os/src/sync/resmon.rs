@@ -19,43 +19,37 @@ pub trait   SyncRes {
 
     ///
     fn acquire(&self) {
+        self.acquire_for(current_task().unwrap().get_tid().unwrap());
+    }
+
+    /// Like [`SyncRes::acquire`], but charges the allocation to `tid`
+    /// instead of the calling task. Needed when a resource is handed off
+    /// directly to a task other than the one running right now, e.g. a
+    /// reader-writer lock waking a queued writer: the handoff happens on
+    /// the unlocking task's stack, but the allocation belongs to the
+    /// task being woken, not to the one doing the waking.
+    fn acquire_for(&self, tid: usize) {
         let resid = self.getid() as usize;
         let curproc = current_process();
         let mut resmon = curproc.resmon.exclusive_access();
-        if resmon.need.len() < current_task().unwrap().get_tid().unwrap() + 1 {
-            let mut value = VecDeque::<u32>::new();
-            value.resize(resmon.avail.len(), 0);
-            resmon.need.resize(current_task().unwrap().get_tid().unwrap() + 1, value);
-        }
-        if resmon.alloc.len() < current_task().unwrap().get_tid().unwrap() + 1 {
-            let mut value = VecDeque::<u32>::new();
-            value.resize(resmon.avail.len(), 0);
-            resmon.alloc.resize(current_task().unwrap().get_tid().unwrap() + 1, value);
-        }
-        let reslen = resmon.avail.len();
-        if resmon.need[0].len() < reslen {
-            for i in 0..resmon.need.len() {
-                resmon.need[i].resize(reslen, 0);
-            }
-        }
-        if resmon.alloc[0].len() < reslen {
-            for i in 0..resmon.alloc.len() {
-                resmon.alloc[i].resize(reslen, 0);
-            }
+        resmon.ensure_capacity(tid);
+        if resid >= resmon.avail.len() {
+            warn!("kernel: SyncRes::acquire_for: task {} acquired unknown res {}", tid, resid);
+            return;
         }
         #[cfg(feature = "debug_syncres")]
         trace!("Pid@{} Acquiring SyncRes: task={}/{} res={:?} res[{}]={}",
                 current_task().unwrap().process.upgrade().unwrap().getpid(),
-                current_task().unwrap().get_tid().unwrap(),
+                tid,
                 resmon.alloc.len(),
                 resmon.avail,
                 resid, resmon.avail[resid],
 
             );
         resmon.avail[resid] -= 1;
-        resmon.alloc[current_task().unwrap().get_tid().unwrap()][resid] += 1;
-        if resmon.need[current_task().unwrap().get_tid().unwrap()][resid] > 0 {
-            resmon.need[current_task().unwrap().get_tid().unwrap()][resid] = 0;
+        resmon.alloc[tid][resid] += 1;
+        if resmon.need[tid][resid] > 0 {
+            resmon.need[tid][resid] = 0;
         }
     }
 
@@ -64,26 +58,10 @@ pub trait   SyncRes {
         let resid = self.getid() as usize;
         let curproc = current_process();
         let mut resmon = curproc.resmon.exclusive_access();
-        if resmon.need.len() < current_task().unwrap().get_tid().unwrap() + 1 {
-            let mut value = VecDeque::<u32>::new();
-            value.resize(resmon.avail.len(), 0);
-            resmon.need.resize(current_task().unwrap().get_tid().unwrap() + 1, value);
-        }
-        if resmon.alloc.len() < current_task().unwrap().get_tid().unwrap() + 1 {
-            let mut value = VecDeque::<u32>::new();
-            value.resize(resmon.avail.len(), 0);
-            resmon.alloc.resize(current_task().unwrap().get_tid().unwrap() + 1, value);
-        }
-        let reslen = resmon.avail.len();
-        if resmon.need[0].len() < reslen {
-            for i in 0..resmon.need.len() {
-                resmon.need[i].resize(reslen, 0);
-            }
-        }
-        if resmon.alloc[0].len() < reslen {
-            for i in 0..resmon.alloc.len() {
-                resmon.alloc[i].resize(reslen, 0);
-            }
+        resmon.ensure_capacity(current_task().unwrap().get_tid().unwrap());
+        if resid >= resmon.avail.len() {
+            warn!("kernel: SyncRes::need: task {} needs unknown res {}", current_task().unwrap().get_tid().unwrap(), resid);
+            return;
         }
         if resmon.need[current_task().unwrap().get_tid().unwrap()][resid] > 0 {
             return;
@@ -101,13 +79,41 @@ pub trait   SyncRes {
         resmon.need[current_task().unwrap().get_tid().unwrap()][resid] += 1;
     }
     
-    ///
+    /// Roll back a pending [`SyncRes::need`] registration, e.g. when a
+    /// `*_timeout` wait gives up before the resource became available.
+    /// Leaves the deadlock matrix as if this task had never asked for it.
+    fn unneed(&self) {
+        let resid = self.getid() as usize;
+        let curproc = current_process();
+        let mut resmon = curproc.resmon.exclusive_access();
+        let tid = current_task().unwrap().get_tid().unwrap();
+        if tid < resmon.need.len() && resid < resmon.need[tid].len() {
+            resmon.need[tid][resid] = 0;
+        }
+    }
+
+    /// Give a held unit of this resource back. Guarded against an
+    /// unbalanced release (e.g. a semaphore `up` with no matching
+    /// `down`): decrementing `alloc[tid][resid]` below `0` would wrap a
+    /// `u32` around to a huge count and poison every later `check()`'s
+    /// budget arithmetic, so it saturates at `0` and logs instead.
+    /// `avail[resid]` is likewise capped so a stray extra release can't
+    /// push it past the resource's originally created count.
     fn release(&self) {
         let resid = self.getid() as usize;
+        let tid = current_task().unwrap().get_tid().unwrap();
         let curproc = current_process();
         let mut resmon = curproc.resmon.exclusive_access();
-        resmon.avail[resid] += 1;
-        resmon.alloc[current_task().unwrap().get_tid().unwrap()][resid] -= 1;
+        if resid >= resmon.avail.len() {
+            warn!("kernel: SyncRes::release: task {} released unknown res {}", tid, resid);
+            return;
+        }
+        if resmon.alloc[tid][resid] == 0 {
+            warn!("kernel: SyncRes::release: task {} released res {} it never held", tid, resid);
+        } else {
+            resmon.alloc[tid][resid] -= 1;
+        }
+        resmon.avail[resid] = resmon.avail[resid].saturating_add(1).min(resmon.created[resid]);
     }
 
     ///
@@ -118,32 +124,9 @@ pub trait   SyncRes {
             return None;
         }
         let mut resmon = curproc.resmon.exclusive_access();
-        if resmon.need.len() < current_task().unwrap().get_tid().unwrap() + 1 {
-            let mut value = VecDeque::<u32>::new();
-            value.resize(resmon.avail.len(), 0);
-            resmon.need.resize(current_task().unwrap().get_tid().unwrap() + 1, value);
-        }
-        if resmon.alloc.len() < current_task().unwrap().get_tid().unwrap() + 1 {
-            let mut value = VecDeque::<u32>::new();
-            value.resize(resmon.avail.len(), 0);
-            resmon.alloc.resize(current_task().unwrap().get_tid().unwrap() + 1, value);
-        }
-        let reslen = resmon.avail.len();
-        if resmon.need[0].len() < reslen {
-            for i in 0..resmon.need.len() {
-                resmon.need[i].resize(reslen, 0);
-            }
-        }
-        if resmon.alloc[0].len() < reslen {
-            for i in 0..resmon.alloc.len() {
-                resmon.alloc[i].resize(reslen, 0);
-            }
-        }
+        resmon.ensure_capacity(current_task().unwrap().get_tid().unwrap());
 
-        let mut finish = [true; 1024];
-        for i in 0..resmon.alloc.len() {
-            finish[i] = false;
-        }
+        let mut finish = alloc::vec![false; resmon.alloc.len()];
         let mut budget = resmon.avail.clone();
         let mut progress = true;
         #[cfg(feature = "debug_syncres")]
@@ -184,8 +167,8 @@ pub trait   SyncRes {
                     progress = true;
                     for r in 0..budget.len() {
                         budget[r] += resmon.alloc[t][r] as i32;
-                        finish[t] = true;
                     }
+                    finish[t] = true;
                     #[cfg(feature = "debug_syncres")]
                     trace!("Pid@{} Deadlock Checking: budget={:?}",
                         current_task().unwrap().process.upgrade().unwrap().getpid(),
@@ -197,11 +180,28 @@ pub trait   SyncRes {
 
         for (i, f) in finish.iter().enumerate() {
             if !f {
+                #[cfg(feature = "debug_syncres")]
+                trace!("Pid@{} Deadlock Detected: cycle={:?}",
+                    current_task().unwrap().process.upgrade().unwrap().getpid(),
+                    resmon.deadlock_cycle(),
+                );
                 return Some(i as u32);
             }
         }
         None
     }
+
+    /// Like [`SyncRes::check`], but instead of stopping at the first
+    /// unfinished task, reports every `(tid, resid)` edge of the
+    /// wait-for cycle: each task that never finishes the banker's-algorithm
+    /// simulation, paired with a resource it's still blocked needing that
+    /// the simulation's budget could never cover. Cross-referencing a
+    /// `resid` in the result against `alloc` tells you who's holding it.
+    fn detect_deadlock_cycle(&self) -> alloc::vec::Vec<(u32, u32)> {
+        let curproc = current_process();
+        let resmon = curproc.resmon.exclusive_access();
+        resmon.deadlock_cycle()
+    }
 }
 
 ///
@@ -209,6 +209,17 @@ pub struct  ResMonitor {
     avail: Available,
     alloc: Allocation,
     need: Need,
+    /// The count each resource was originally `create_res`'d with,
+    /// indexed the same as `avail`; caps [`SyncRes::release`]'s
+    /// increment so a stray extra release can't inflate `avail` past
+    /// what the resource actually has.
+    created: VecDeque<i32>,
+    /// Resource ids freed by [`ResMonitor::free_res`], available for
+    /// [`ResMonitor::create_res`] to hand back out before it grows the
+    /// matrices with a brand new column. Without this, a process that
+    /// creates and drops a lock in a loop would grow `avail`/`alloc`/`need`
+    /// by one column every iteration, forever.
+    free_list: VecDeque<u32>,
 }
 
 impl ResMonitor {
@@ -218,19 +229,127 @@ impl ResMonitor {
             avail: VecDeque::<i32>::new(),
             alloc: VecDeque::<VecDeque::<u32>>::new(),
             need: VecDeque::<VecDeque::<u32>>::new(),
+            created: VecDeque::<i32>::new(),
+            free_list: VecDeque::new(),
         }
     }
 
-    ///
+    /// Allocate a resource id for `num` units, reusing one off
+    /// [`ResMonitor::free_res`]'s free list before growing the matrices
+    /// with a new column.
     pub fn create_res(&mut self, num: u32) -> u32 {
+        if let Some(resid) = self.free_list.pop_front() {
+            self.avail[resid as usize] = num as i32;
+            self.created[resid as usize] = num as i32;
+            return resid;
+        }
         self.avail.push_back(num as i32);
+        self.created.push_back(num as i32);
         (self.avail.len() - 1) as u32
     }
 
+    /// Retire `resid`, zeroing its column in every matrix so a stale
+    /// `alloc`/`need` entry from before the drop can't be mistaken for a
+    /// live one if the id gets handed back out by a later `create_res`.
+    /// An out-of-range `resid` is a no-op, the same defensive posture
+    /// `SyncRes::release` and friends take above.
+    pub fn free_res(&mut self, resid: u32) {
+        let resid = resid as usize;
+        if resid >= self.avail.len() {
+            return;
+        }
+        self.avail[resid] = 0;
+        self.created[resid] = 0;
+        for row in self.alloc.iter_mut() {
+            if resid < row.len() {
+                row[resid] = 0;
+            }
+        }
+        for row in self.need.iter_mut() {
+            if resid < row.len() {
+                row[resid] = 0;
+            }
+        }
+        self.free_list.push_back(resid as u32);
+    }
+
+    /// Grow `need`/`alloc` so row `tid` and every column up to
+    /// `avail.len()` exist, without clobbering what's already there.
+    /// Called before `acquire_for`/`need`/`check` index into either
+    /// matrix, so a never-before-seen tid or a resource created after
+    /// some tids already exist both grow the matrices instead of
+    /// panicking.
+    ///
+    /// Resizing every row's column count, not just row 0's, matters: if
+    /// a resource is created between two calls that only ever widened
+    /// the most-recently-touched row, earlier rows would stay ragged and
+    /// fall out of sync with `avail.len()`.
+    fn ensure_capacity(&mut self, tid: usize) {
+        if self.need.len() < tid + 1 {
+            self.need.resize(tid + 1, VecDeque::new());
+        }
+        if self.alloc.len() < tid + 1 {
+            self.alloc.resize(tid + 1, VecDeque::new());
+        }
+        let reslen = self.avail.len();
+        for row in self.need.iter_mut() {
+            if row.len() < reslen {
+                row.resize(reslen, 0);
+            }
+        }
+        for row in self.alloc.iter_mut() {
+            if row.len() < reslen {
+                row.resize(reslen, 0);
+            }
+        }
+    }
+
     ///
     pub fn dump_res(&self) {
         trace!("AVAIL: {:?}", self.avail);
         trace!("ALLOC: {:?}", self.alloc);
         trace!("NEED: {:?}", self.need);
     }
+
+    /// Run the same banker's-algorithm simulation [`SyncRes::check`]
+    /// does, but instead of returning just the first unfinished task,
+    /// collect every `(tid, resid)` pair where `tid` never finishes and
+    /// `resid` is one of the resources blocking it -- the wait-for edges
+    /// that make up the deadlock cycle.
+    pub fn deadlock_cycle(&self) -> alloc::vec::Vec<(u32, u32)> {
+        let mut finish = alloc::vec![false; self.alloc.len()];
+        let mut budget = self.avail.clone();
+        let mut progress = true;
+        while progress {
+            progress = false;
+            for t in 0..self.alloc.len() {
+                if finish[t] {
+                    continue;
+                }
+                let fulfil = (0..budget.len()).all(|r| {
+                    !(self.need[t][r] as i32 > 0 && self.need[t][r] as i32 > budget[r])
+                });
+                if fulfil {
+                    progress = true;
+                    for r in 0..budget.len() {
+                        budget[r] += self.alloc[t][r] as i32;
+                    }
+                    finish[t] = true;
+                }
+            }
+        }
+
+        let mut cycle = alloc::vec::Vec::new();
+        for (t, f) in finish.iter().enumerate() {
+            if *f {
+                continue;
+            }
+            for r in 0..budget.len() {
+                if self.need[t][r] as i32 > 0 && self.need[t][r] as i32 > budget[r] {
+                    cycle.push((t as u32, r as u32));
+                }
+            }
+        }
+        cycle
+    }
 }
\ No newline at end of file
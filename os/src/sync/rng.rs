@@ -0,0 +1,47 @@
+//! A minimal kernel PRNG for [`crate::syscall::process::sys_getrandom`] --
+//! xorshift64, reseeded from [`crate::timer::get_time_us`] plus a
+//! per-boot entropy counter so two back-to-back calls never see the same
+//! state even if `get_time_us()` hasn't ticked between them.
+
+use super::UPSafeCell;
+use crate::timer::get_time_us;
+use lazy_static::lazy_static;
+
+struct Xorshift64 {
+    state: u64,
+    entropy: u64,
+}
+
+impl Xorshift64 {
+    fn next_u64(&mut self) -> u64 {
+        self.entropy = self.entropy.wrapping_add(1);
+        let mut x = self.state ^ (get_time_us() as u64) ^ self.entropy;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+}
+
+lazy_static! {
+    /// Seeded with whatever `get_time_us()` reads at first use -- good
+    /// enough for scattering userspace buffers, not for anything that
+    /// needs cryptographic unpredictability.
+    static ref RNG: UPSafeCell<Xorshift64> = unsafe {
+        UPSafeCell::new(Xorshift64 {
+            state: get_time_us() as u64 | 1,
+            entropy: 0,
+        })
+    };
+}
+
+/// Fill `buf` with pseudo-random bytes.
+pub fn fill_bytes(buf: &mut [u8]) {
+    let mut rng = RNG.exclusive_access();
+    let mut chunks = buf.chunks_mut(8);
+    for chunk in &mut chunks {
+        let word = rng.next_u64().to_le_bytes();
+        chunk.copy_from_slice(&word[..chunk.len()]);
+    }
+}
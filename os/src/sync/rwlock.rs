@@ -0,0 +1,197 @@
+//! RwLock
+
+use crate::sync::UPSafeCell;
+use crate::sync::{SyncRes, DEAD_LOCK};
+use crate::task::{block_current_and_run_next, current_process, current_task, wakeup_task, TaskControlBlock};
+use alloc::{collections::VecDeque, sync::Arc};
+
+/// Reader-writer lock, modelled on top of `SyncRes` as one resource with
+/// `max_readers` instances: a reader acquires one instance, a writer
+/// acquires all of them at once so it only proceeds once no reader holds any.
+pub struct RwLock {
+    inner: UPSafeCell<RwLockInner>,
+    resid: u32,
+    max_readers: u32,
+}
+
+pub struct RwLockInner {
+    readers: u32,
+    writer: bool,
+    /// number of writers currently queued; while non-zero, new readers
+    /// must wait behind them to avoid writer starvation
+    waiting_writers: u32,
+    read_wait_queue: VecDeque<Arc<TaskControlBlock>>,
+    write_wait_queue: VecDeque<Arc<TaskControlBlock>>,
+}
+
+impl RwLock {
+    /// Create a new rwlock allowing up to `max_readers` concurrent readers
+    pub fn new(max_readers: u32) -> Self {
+        #[cfg(feature = "debug_sem")]
+        trace!("kernel: RwLock::new");
+        let curproc = current_process();
+        let mut resmon = curproc.resmon.exclusive_access();
+        Self {
+            inner: unsafe {
+                UPSafeCell::new(RwLockInner {
+                    readers: 0,
+                    writer: false,
+                    waiting_writers: 0,
+                    read_wait_queue: VecDeque::new(),
+                    write_wait_queue: VecDeque::new(),
+                })
+            },
+            resid: resmon.create_res(max_readers),
+            max_readers,
+        }
+    }
+
+    /// Acquire a read lock, blocking behind any writer that holds or is
+    /// queued for the lock
+    pub fn read_lock(&self) -> i32 {
+        #[cfg(feature = "debug_sem")]
+        trace!("kernel: RwLock::read_lock");
+        let mut inner = self.inner.exclusive_access();
+        if inner.writer || inner.waiting_writers > 0 {
+            inner.read_wait_queue.push_back(current_task().unwrap());
+            drop(inner);
+            self.need();
+            if let Some(_) = self.check() {
+                return DEAD_LOCK;
+            }
+            block_current_and_run_next();
+        } else {
+            inner.readers += 1;
+            self.acquire();
+        }
+        0
+    }
+
+    /// Like [`RwLock::read_lock`], but returns `false` immediately
+    /// instead of blocking if a writer holds or is queued for the lock
+    pub fn try_read_lock(&self) -> bool {
+        #[cfg(feature = "debug_sem")]
+        trace!("kernel: RwLock::try_read_lock");
+        let mut inner = self.inner.exclusive_access();
+        if inner.writer || inner.waiting_writers > 0 {
+            return false;
+        }
+        inner.readers += 1;
+        self.acquire();
+        true
+    }
+
+    /// Release a read lock
+    pub fn read_unlock(&self) {
+        #[cfg(feature = "debug_sem")]
+        trace!("kernel: RwLock::read_unlock");
+        let mut inner = self.inner.exclusive_access();
+        assert!(inner.readers > 0);
+        inner.readers -= 1;
+        self.release();
+        if inner.readers == 0 {
+            if let Some(writer) = inner.write_wait_queue.pop_front() {
+                inner.writer = true;
+                inner.waiting_writers -= 1;
+                drop(inner);
+                let tid = writer.get_tid().unwrap();
+                for _ in 0..self.max_readers {
+                    self.acquire_for(tid);
+                }
+                wakeup_task(writer);
+            }
+        }
+    }
+
+    /// Acquire the write lock, requesting all `max_readers` instances so
+    /// the writer only runs once no reader holds any of them; marks
+    /// itself queued first so late-arriving readers block behind it
+    pub fn write_lock(&self) -> i32 {
+        #[cfg(feature = "debug_sem")]
+        trace!("kernel: RwLock::write_lock");
+        let mut inner = self.inner.exclusive_access();
+        if inner.writer || inner.readers > 0 {
+            inner.waiting_writers += 1;
+            inner.write_wait_queue.push_back(current_task().unwrap());
+            drop(inner);
+            self.need();
+            if let Some(_) = self.check() {
+                return DEAD_LOCK;
+            }
+            block_current_and_run_next();
+        } else {
+            inner.writer = true;
+            drop(inner);
+            for _ in 0..self.max_readers {
+                self.acquire();
+            }
+        }
+        0
+    }
+
+    /// Like [`RwLock::write_lock`], but returns `false` immediately
+    /// instead of blocking if the lock is held or already queued
+    pub fn try_write_lock(&self) -> bool {
+        #[cfg(feature = "debug_sem")]
+        trace!("kernel: RwLock::try_write_lock");
+        let mut inner = self.inner.exclusive_access();
+        if inner.writer || inner.readers > 0 {
+            return false;
+        }
+        inner.writer = true;
+        drop(inner);
+        for _ in 0..self.max_readers {
+            self.acquire();
+        }
+        true
+    }
+
+    /// Release the write lock, waking a queued writer if there is one,
+    /// otherwise releasing every queued reader
+    pub fn write_unlock(&self) {
+        #[cfg(feature = "debug_sem")]
+        trace!("kernel: RwLock::write_unlock");
+        let mut inner = self.inner.exclusive_access();
+        assert!(inner.writer);
+        inner.writer = false;
+        for _ in 0..self.max_readers {
+            self.release();
+        }
+        if let Some(writer) = inner.write_wait_queue.pop_front() {
+            inner.writer = true;
+            inner.waiting_writers -= 1;
+            drop(inner);
+            let tid = writer.get_tid().unwrap();
+            for _ in 0..self.max_readers {
+                self.acquire_for(tid);
+            }
+            wakeup_task(writer);
+        } else {
+            let readers: alloc::vec::Vec<_> = inner.read_wait_queue.drain(..).collect();
+            inner.readers = readers.len() as u32;
+            drop(inner);
+            for reader in &readers {
+                self.acquire_for(reader.get_tid().unwrap());
+            }
+            for reader in readers {
+                wakeup_task(reader);
+            }
+        }
+    }
+}
+
+impl SyncRes for RwLock {
+    fn getid(&self) -> u32 {
+        self.resid
+    }
+}
+
+impl Drop for RwLock {
+    /// Hand `resid` back to [`super::resmon::ResMonitor`]'s free list, the
+    /// same as [`super::mutex::MutexSpin`]'s impl, so a later
+    /// `RwLock::new` in the same process can reuse it.
+    fn drop(&mut self) {
+        let curproc = current_process();
+        curproc.resmon.exclusive_access().free_res(self.resid);
+    }
+}
@@ -5,6 +5,11 @@ use crate::task::{block_current_and_run_next, current_task, wakeup_task, TaskCon
 use crate::task::{current_process};
 use alloc::{collections::VecDeque, sync::Arc};
 use crate::sync::{ SyncRes, DEAD_LOCK };
+use crate::sync::timeout::{deadline_after, TimeoutWaitable, TIMEOUT_QUEUE, TIMED_OUT};
+
+/// Returned by [`Semaphore::try_down`] when the count would drop below
+/// zero, instead of enqueuing and blocking like [`Semaphore::down`] does
+pub const WOULD_BLOCK: i32 = -0x5742;
 
 /// semaphore structure
 pub struct Semaphore {
@@ -15,7 +20,11 @@ pub struct Semaphore {
 
 pub struct SemaphoreInner {
     pub count: isize,
-    pub wait_queue: VecDeque<Arc<TaskControlBlock>>,
+    /// Waiters queued by [`Semaphore::down_n`], paired with how many
+    /// units each one is still waiting for. [`Semaphore::up_n`] only
+    /// pops a waiter off the front once enough units have accumulated
+    /// to satisfy its request.
+    pub wait_queue: VecDeque<(Arc<TaskControlBlock>, usize)>,
 }
 
 impl Semaphore {
@@ -32,49 +41,130 @@ impl Semaphore {
                     wait_queue: VecDeque::new(),
                 })
             },
-            resid: resmon.create_res(res_count as u32), 
+            resid: resmon.create_res(res_count as u32),
         }
     }
 
     /// up operation of semaphore
     pub fn up(&self) {
+        self.up_n(1);
+    }
+
+    /// Release `n` units at once, waking every queued waiter whose
+    /// request the accumulated count can now satisfy, in queue order.
+    pub fn up_n(&self, n: usize) {
         #[cfg(feature = "debug_sem")]
-        trace!("kernel: Semaphore::up");
+        trace!("kernel: Semaphore::up_n");
         let mut inner = self.inner.exclusive_access();
-        inner.count += 1;
-        self.acquire();
-        if inner.count <= 0 {
-            if let Some(task) = inner.wait_queue.pop_front() {
-                wakeup_task(task);
+        inner.count += n as isize;
+        // `up` gives units back -- `release()`, not `acquire()` (which
+        // would have this task's own `alloc` entry grow on a release,
+        // inverting the resmon accounting the deadlock detector's
+        // banker's-algorithm `check()` relies on).
+        for _ in 0..n {
+            self.release();
+        }
+        while let Some((_, need_n)) = inner.wait_queue.front() {
+            if *need_n as isize > inner.count {
+                break;
+            }
+            let (task, need_n) = inner.wait_queue.pop_front().unwrap();
+            inner.count -= need_n as isize;
+            // Hand the units straight to the waiter being woken, the
+            // same way `MutexBlocking::unlock` hands its lock to the
+            // waiter it wakes: `acquire_for` moves them from `avail`
+            // into the waiter's own `alloc` and clears the `need` entry
+            // `down_n` registered when it queued, instead of leaving
+            // them credited to nobody.
+            let tid = task.get_tid().unwrap();
+            for _ in 0..need_n {
+                self.acquire_for(tid);
             }
+            wakeup_task(task);
         }
     }
 
     /// down operation of semaphore
     pub fn down(&self) -> i32 {
+        self.down_n(1)
+    }
+
+    /// Block until `n` units are available, then take all of them at
+    /// once. Matches [`Semaphore::down`]'s deadlock-detection and
+    /// blocking pattern, but the units are reserved atomically: a
+    /// `down_n(2)` can never be satisfied by two separate `up(1)`s each
+    /// waking a different task.
+    pub fn down_n(&self, n: usize) -> i32 {
         #[cfg(feature = "debug_sem")]
-        trace!("kernel: Semaphore::down");
+        trace!("kernel: Semaphore::down_n");
         #[cfg(feature = "debug_sem")]
         {
             let curproc = current_process();
             let resmon = curproc.resmon.exclusive_access();
             resmon.dump_res();
-            drop(resmon);    
+            drop(resmon);
         }
 
         let mut inner = self.inner.exclusive_access();
-        inner.count -= 1;
-        if inner.count < 0 {
-            self.need();
-            if let Some(_) = self.check() {
-                inner.count += 1;
-                return DEAD_LOCK;
+        if n as isize <= inner.count {
+            inner.count -= n as isize;
+            drop(inner);
+            for _ in 0..n {
+                self.acquire();
             }
-            inner.wait_queue.push_back(current_task().unwrap());
+            return 0;
+        }
+        self.need();
+        if let Some(_) = self.check() {
+            return DEAD_LOCK;
+        }
+        inner.wait_queue.push_back((current_task().unwrap(), n));
+        drop(inner);
+        block_current_and_run_next();
+        0
+    }
+
+    /// Like [`Semaphore::down`], but never enqueues onto `wait_queue` and
+    /// never blocks: if the count would drop below zero, returns
+    /// [`WOULD_BLOCK`] immediately and leaves `count` untouched, so
+    /// callers can poll a semaphore from a context that must not sleep.
+    pub fn try_down(&self) -> i32 {
+        #[cfg(feature = "debug_sem")]
+        trace!("kernel: Semaphore::try_down");
+        let mut inner = self.inner.exclusive_access();
+        if inner.count <= 0 {
+            return WOULD_BLOCK;
+        }
+        inner.count -= 1;
+        drop(inner);
+        self.acquire();
+        0
+    }
+
+    /// Like [`Semaphore::down`], but gives up and returns [`TIMED_OUT`]
+    /// if `ticks` pass before the semaphore is signaled
+    pub fn down_timeout(self: &Arc<Self>, ticks: usize) -> i32 {
+        #[cfg(feature = "debug_sem")]
+        trace!("kernel: Semaphore::down_timeout");
+        let mut inner = self.inner.exclusive_access();
+        if 1 <= inner.count {
+            inner.count -= 1;
             drop(inner);
-            block_current_and_run_next();
-        } else {
             self.acquire();
+            return 0;
+        }
+        self.need();
+        if let Some(_) = self.check() {
+            return DEAD_LOCK;
+        }
+        let task = current_task().unwrap();
+        inner.wait_queue.push_back((task.clone(), 1));
+        drop(inner);
+        let deadline = deadline_after(ticks);
+        TIMEOUT_QUEUE.register(deadline, task.clone(), self.clone() as Arc<dyn TimeoutWaitable>);
+        block_current_and_run_next();
+        if TIMEOUT_QUEUE.take_timed_out(&task) {
+            return TIMED_OUT;
         }
         0
     }
@@ -86,3 +176,26 @@ impl SyncRes for Semaphore {
     }
 }
 
+impl Drop for Semaphore {
+    /// Hand `resid` back to [`super::resmon::ResMonitor`]'s free list, the
+    /// same as [`super::mutex::MutexSpin`]'s impl, so a later
+    /// `Semaphore::new` in the same process can reuse it.
+    fn drop(&mut self) {
+        let curproc = current_process();
+        curproc.resmon.exclusive_access().free_res(self.resid);
+    }
+}
+
+impl TimeoutWaitable for Semaphore {
+    fn cancel_wait(&self, task: &Arc<TaskControlBlock>) -> bool {
+        let mut inner = self.inner.exclusive_access();
+        if let Some(pos) = inner.wait_queue.iter().position(|(t, _)| Arc::ptr_eq(t, task)) {
+            inner.wait_queue.remove(pos);
+            self.unneed();
+            true
+        } else {
+            false
+        }
+    }
+}
+
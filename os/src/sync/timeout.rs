@@ -0,0 +1,135 @@
+//! Shared timer-queue infrastructure for deadline-based blocking, used by
+//! [`crate::sync::Semaphore::down_timeout`],
+//! [`crate::sync::MutexBlocking::lock_timeout`], and the bare
+//! [`SleepTimer`] behind `sys_sleep`.
+
+use super::UPSafeCell;
+use crate::task::{wakeup_task, TaskControlBlock};
+use crate::timer::get_time_us;
+use alloc::{collections::VecDeque, sync::Arc};
+use lazy_static::lazy_static;
+
+/// Returned by a `*_timeout` wait when its deadline passes before the
+/// resource became available
+pub const TIMED_OUT: i32 = -0x7100;
+
+/// A blocking primitive a task can be parked on with a deadline.
+pub trait TimeoutWaitable: Sync + Send {
+    /// Try to pull `task` back out of this primitive's own wait queue
+    /// before it is woken normally. Returns `true` if `task` was still
+    /// parked there (and has now been un-parked and had its pending
+    /// resource accounting rolled back), `false` if the primitive had
+    /// already handed it the resource and woken it through the normal
+    /// `up()`/`unlock()` path.
+    ///
+    /// Both this and the normal wakeup path take the primitive's own
+    /// inner lock, so a task is removed from exactly one of {the
+    /// primitive's wait_queue, the timer queue} even if both a release
+    /// and the deadline happen "at the same time".
+    fn cancel_wait(&self, task: &Arc<TaskControlBlock>) -> bool;
+}
+
+struct TimerEntry {
+    deadline: usize,
+    task: Arc<TaskControlBlock>,
+    waitable: Arc<dyn TimeoutWaitable>,
+}
+
+struct TimeoutQueueInner {
+    entries: VecDeque<TimerEntry>,
+    /// Tasks the timer queue won the race for, waiting to be claimed by
+    /// the `*_timeout` call that is about to resume after being woken
+    timed_out: VecDeque<Arc<TaskControlBlock>>,
+}
+
+/// The global deadline queue. The timer interrupt handler should call
+/// [`TimeoutQueue::expire`] with the current [`get_time_us`] reading once
+/// per tick.
+pub struct TimeoutQueue {
+    inner: UPSafeCell<TimeoutQueueInner>,
+}
+
+lazy_static! {
+    /// Every `*_timeout` wait registers its deadline here
+    pub static ref TIMEOUT_QUEUE: TimeoutQueue = TimeoutQueue::new();
+}
+
+impl TimeoutQueue {
+    fn new() -> Self {
+        Self {
+            inner: unsafe {
+                UPSafeCell::new(TimeoutQueueInner {
+                    entries: VecDeque::new(),
+                    timed_out: VecDeque::new(),
+                })
+            },
+        }
+    }
+
+    /// Register `task` to be un-parked from `waitable` if `deadline` (an
+    /// absolute [`get_time_us`] time, as from [`deadline_after`]) passes
+    /// before it is woken normally
+    pub fn register(&self, deadline: usize, task: Arc<TaskControlBlock>, waitable: Arc<dyn TimeoutWaitable>) {
+        self.inner.exclusive_access().entries.push_back(TimerEntry {
+            deadline,
+            task,
+            waitable,
+        });
+    }
+
+    /// Called from the timer interrupt path with the current
+    /// [`get_time_us`] reading
+    pub fn expire(&self, now: usize) {
+        let mut inner = self.inner.exclusive_access();
+        let expired: VecDeque<TimerEntry> = {
+            let mut due = VecDeque::new();
+            let mut pending = VecDeque::new();
+            while let Some(entry) = inner.entries.pop_front() {
+                if entry.deadline <= now {
+                    due.push_back(entry);
+                } else {
+                    pending.push_back(entry);
+                }
+            }
+            inner.entries = pending;
+            due
+        };
+        for entry in expired {
+            if entry.waitable.cancel_wait(&entry.task) {
+                inner.timed_out.push_back(entry.task.clone());
+                wakeup_task(entry.task);
+            }
+        }
+    }
+
+    /// Called by a `*_timeout` wait right after it resumes, to find out
+    /// whether it was woken by its deadline rather than by the resource
+    /// becoming available
+    pub fn take_timed_out(&self, task: &Arc<TaskControlBlock>) -> bool {
+        let mut inner = self.inner.exclusive_access();
+        if let Some(pos) = inner.timed_out.iter().position(|t| Arc::ptr_eq(t, task)) {
+            inner.timed_out.remove(pos);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// An absolute deadline `ticks` microseconds in the future, on the
+/// [`get_time_us`] clock
+pub fn deadline_after(ticks: usize) -> usize {
+    get_time_us() + ticks
+}
+
+/// Trivial [`TimeoutWaitable`] for a bare sleep (`sys_sleep`): unlike
+/// `MutexBlocking`/`Semaphore`/`Condvar`, there's no other wait queue a
+/// sleeping task could be parked on, so there's nothing to race against
+/// and cancelling always succeeds.
+pub struct SleepTimer;
+
+impl TimeoutWaitable for SleepTimer {
+    fn cancel_wait(&self, _task: &Arc<TaskControlBlock>) -> bool {
+        true
+    }
+}
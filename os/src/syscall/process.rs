@@ -1,21 +1,59 @@
 //! Process management syscalls
 //!
 use alloc::sync::Arc;
+use alloc::vec::Vec;
 
 use crate::{
-    config::{MAX_SYSCALL_NUM, BIG_STRIDE},
+    config::{MAX_SYSCALL_NUM, BIG_STRIDE, PAGE_SIZE},
     fs::{open_file, OpenFlags},
-    mm::{translated_refmut, translated_str},
+    mm::{translated_str},
+    syscall::seccomp,
     task::{
-        add_task, current_task, current_user_token, exit_current_and_run_next,
-        suspend_current_and_run_next, TaskStatus,
+        add_task, block_current_and_run_next, current_task, current_user_token,
+        exit_current_and_run_next, suspend_current_and_run_next, MmapRegion, SchedPolicy,
+        TaskControlBlock, TaskStatus,
     },
     mm::{*},
-    timer::get_time_us,
+    timer::{get_time_us, get_time_ns},
+    sync::timeout::{deadline_after, TimeoutWaitable, TIMEOUT_QUEUE, SleepTimer},
 };
 
+/// Syscall numbers for the handlers in this file, as consulted by
+/// [`seccomp::enforce`] before each one runs. `sys_exit` isn't included:
+/// it never returns, so there's no way to hand a `Deny` errno back to
+/// userspace, and a `Kill` disposition has no observable effect on a task
+/// that's exiting anyway. `sys_get_time`, `sys_task_info` and
+/// `sys_ptrace` have no entry here either: they don't call
+/// [`seccomp::enforce`], so an installed filter does not apply to them.
+const SYSCALL_YIELD: usize = 124;
+const SYSCALL_GETPID: usize = 172;
+const SYSCALL_FORK: usize = 220;
+const SYSCALL_EXEC: usize = 221;
+const SYSCALL_WAITPID: usize = 260;
+const SYSCALL_CLONE: usize = 560;
+const SYSCALL_SBRK: usize = 214;
+const SYSCALL_SPAWN: usize = 400;
+const SYSCALL_SET_PRIORITY: usize = 140;
+const SYSCALL_MMAP: usize = 222;
+const SYSCALL_MUNMAP: usize = 215;
+const SYSCALL_MSYNC: usize = 227;
+const SYSCALL_SLEEP: usize = 101;
+const SYSCALL_CLOCK_GETTIME: usize = 113;
+const SYSCALL_TIMES: usize = 153;
+const SYSCALL_GETRANDOM: usize = 278;
+const SYSCALL_SET_CPU_LIMIT: usize = 1040;
+const SYSCALL_SCHED_SETAFFINITY: usize = 122;
+const SYSCALL_SCHED_GETAFFINITY: usize = 123;
+
+/// `clk_id` values accepted by [`sys_clock_gettime`]: arbitrary, possibly
+/// backwards-jumping wall-clock time.
+pub const CLOCK_REALTIME: usize = 0;
+/// `clk_id` value accepted by [`sys_clock_gettime`]: time that only ever
+/// moves forward, suitable for measuring elapsed intervals.
+pub const CLOCK_MONOTONIC: usize = 1;
+
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone)]
 ///
 pub struct TimeVal {
     ///
@@ -24,8 +62,37 @@ pub struct TimeVal {
     pub usec: usize,
 }
 
+/// A `(seconds, nanoseconds)` pair, POSIX-`timespec`-style, as written
+/// back by [`sys_clock_gettime`].
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct TimeSpec {
+    /// whole seconds
+    pub sec: u64,
+    /// nanoseconds past `sec`, always in `0..1_000_000_000`
+    pub nsec: u64,
+}
+
+/// Per-task CPU time, POSIX-`times(2)`-style, as written back by
+/// [`sys_times`]. Both fields are in microseconds rather than clock
+/// ticks, since there's no `CLOCKS_PER_SEC`-equivalent conversion factor
+/// defined anywhere in this tree.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct Times {
+    /// Time this task has spent running its own code, per
+    /// [`crate::task::TaskControlBlock::schedule_in`]/[`schedule_out`](crate::task::TaskControlBlock::schedule_out)
+    pub utime: usize,
+    /// Time spent in-kernel on this task's behalf, distinct from `utime`
+    /// -- always `0`, since `schedule_in`/`schedule_out` only bracket
+    /// the task's own running interval and nothing in this tree
+    /// separately times kernel-side work done for a blocked task.
+    pub stime: usize,
+}
+
 /// Task information
 #[allow(dead_code)]
+#[derive(Copy, Clone)]
 pub struct TaskInfo {
     /// Task status in it's life cycle
     pub status: TaskStatus,
@@ -35,6 +102,57 @@ pub struct TaskInfo {
     pub time: usize,
 }
 
+/// Copy `core::mem::size_of::<T>()` bytes out of user space at `vaddr`,
+/// translating through `token`'s page table one page at a time so a value
+/// that straddles a page boundary is still read correctly
+pub(crate) fn read_object<T: Copy>(token: usize, vaddr: usize) -> T {
+    let page_table = PageTable::from_token(token);
+    let len = core::mem::size_of::<T>();
+    let mut buf = alloc::vec![0u8; len];
+    let mut copied = 0;
+    while copied < len {
+        let va = VirtAddr::from(vaddr + copied);
+        let page_off = va.page_offset();
+        let ppn = page_table.translate(va.floor()).unwrap().ppn();
+        let chunk = core::cmp::min(len - copied, PAGE_SIZE - page_off);
+        buf[copied..copied + chunk]
+            .copy_from_slice(&ppn.get_bytes_array()[page_off..page_off + chunk]);
+        copied += chunk;
+    }
+    unsafe { (buf.as_ptr() as *const T).read_unaligned() }
+}
+
+/// Translate `vaddr` under `token`'s page table to the physical address
+/// it currently maps to, the way [`sys_fstat`](crate::syscall::fs::sys_fstat)
+/// and friends translate a single pointer inline -- pulled out here since
+/// [`crate::syscall::sync::sys_futex`] needs the physical address itself
+/// as a wait-queue key, not the bytes it points to.
+pub(crate) fn translated_phys_addr(token: usize, vaddr: usize) -> usize {
+    let page_table = PageTable::from_token(token);
+    let va = VirtAddr::from(vaddr);
+    let ppn = page_table.translate(va.floor()).unwrap().ppn();
+    usize::from(PhysAddr::from(ppn)) + va.page_offset()
+}
+
+/// Write `*val` into user space at `vaddr`, translating through `token`'s
+/// page table one page at a time so a value that straddles a page
+/// boundary is still written correctly
+fn write_object<T: Copy>(token: usize, vaddr: usize, val: &T) {
+    let page_table = PageTable::from_token(token);
+    let len = core::mem::size_of::<T>();
+    let src = unsafe { core::slice::from_raw_parts(val as *const T as *const u8, len) };
+    let mut copied = 0;
+    while copied < len {
+        let va = VirtAddr::from(vaddr + copied);
+        let page_off = va.page_offset();
+        let ppn = page_table.translate(va.floor()).unwrap().ppn();
+        let chunk = core::cmp::min(len - copied, PAGE_SIZE - page_off);
+        ppn.get_bytes_array()[page_off..page_off + chunk]
+            .copy_from_slice(&src[copied..copied + chunk]);
+        copied += chunk;
+    }
+}
+
 /// task exits and submit an exit code
 pub fn sys_exit(exit_code: i32) -> ! {
     #[cfg(feature="debug_exit")]
@@ -43,25 +161,205 @@ pub fn sys_exit(exit_code: i32) -> ! {
     panic!("Unreachable in sys_exit!");
 }
 
+/// `sys_exit_group(exit_code)` -- terminate every thread of the calling
+/// process and record `exit_code` as the process's own, as opposed to
+/// `sys_exit` above which (per its doc comment in a multithreaded
+/// process) should only take down the current thread -- can't land from
+/// this file. It needs a process-level thread list to iterate and tear
+/// down, the same list `sys_waittid`'s note near `CLONE_FILES` below
+/// already flags as missing: `TaskControlBlock::clone_task` in
+/// `crate::task::task` references `self.process.upgrade()` and
+/// `process.new_thread()`, but `TaskControlBlock` declares no `process`
+/// field, and no `ProcessControlBlock` type (which is where that thread
+/// list, and a shared process exit code `sys_waitpid` could read back,
+/// would live) exists anywhere in this tree. `sys_exit` itself can't even
+/// properly implement "only this thread" without that type either --
+/// right now it just calls the single-task `exit_current_and_run_next`,
+/// which has no notion of "other threads of the same process" to leave
+/// running.
+
+/// `sys_kill(pid, signal) -> isize` -- deliver a terminate request to
+/// another pid, can't land from this file. It needs to locate an
+/// arbitrary target task by pid, but the only task handle this tree can
+/// reach is the calling task's own `children` list (see
+/// `sys_waitpid` below); there's no pid-indexed registry of every live
+/// task (a `pid2task`/`TASK_MANAGER`-style map, normally owned by
+/// `task/manager.rs`) to look an unrelated pid up in, and no such file
+/// exists in this snapshot.
+///
+/// The intended shape, for whoever adds that registry: look `pid` up,
+/// return -1 if it's not found; for a `SIGKILL`-equivalent `signal`, set
+/// a `killed: bool` flag on the target's `TaskControlBlockInner` (it has
+/// no such field yet either) instead of tearing it down inline, so the
+/// kill takes effect the next time that task re-enters the kernel -- the
+/// trap entry path would need to check the flag before dispatching the
+/// syscall and call `exit_current_and_run_next` with a conventional
+/// negative exit code on its behalf. Killing your own pid should just
+/// delegate to the same exit path `sys_exit` uses above. A killed
+/// child's zombie would then flow through the existing `sys_waitpid`
+/// reaping logic unchanged.
+///
+/// Process groups (`sys_setpgid`/`sys_kill(-pgid, signal)`) build
+/// directly on top of `sys_kill` and so are blocked by the same missing
+/// pid2task registry -- broadcasting to "every process in group `pgid`"
+/// still needs to enumerate every live task by something other than the
+/// calling task's own `children`. They'd also need a `pgid: usize` field
+/// on `TaskControlBlockInner` (not there yet, inherited across fork the
+/// same way `cwd` is) to track group membership in the first place.
+/// `sys_setpgid` alone, with no registry to broadcast against, would be
+/// storing a field nothing else can use yet, so there's nothing
+/// meaningful to land here before `sys_kill`'s registry exists.
+
+/// `sys_yield_to(tid) -> isize` -- yield and hint the scheduler to run
+/// `tid` next if it's runnable, can't land from this file for the same
+/// reason `sys_kill` above can't: there's no tid-indexed registry to
+/// look an arbitrary `tid` up in. `sys_yield` just below only ever
+/// touches the calling task's own context plus whatever `add_task`
+/// (queued elsewhere) picks next -- neither gives this file a way to
+/// find "the task with this tid" among every live task, runnable or
+/// not, to check it's actually runnable before hinting the scheduler
+/// towards it.
+///
+/// The intended shape, for whoever adds that registry: look `tid` up,
+/// and if it's missing or not `Ready`, fall straight through to the
+/// same path `sys_yield` takes below. Otherwise, the scheduler's ready
+/// queue (also not in this tree) would need a "run this one next"
+/// override distinct from its normal pick -- a one-shot hint consumed
+/// on the very next pick, not a standing priority change, so it doesn't
+/// leak into later scheduling decisions the way raising `tid`'s stride
+/// priority would.
+
 /// current task gives up resources for other tasks
 pub fn sys_yield() -> isize {
     #[cfg(feature="debug_exit")]
     trace!("kernel:pid[{}] sys_yield", current_task().unwrap().pid.0);
+    if let Some(result) = seccomp::enforce(SYSCALL_YIELD) {
+        return result;
+    }
+    check_traced();
     suspend_current_and_run_next();
     0
 }
 
+/// Block the calling task for at least `ms` milliseconds, waking it from
+/// the timer queue rather than having it busy-yield on `sys_get_time`. A
+/// `ms` of 0 just yields once, like [`sys_yield`], since there's nothing
+/// for a zero-length deadline to wait out. Multiple sleepers wake in
+/// deadline order because [`TIMEOUT_QUEUE`] expires entries in deadline
+/// order.
+pub fn sys_sleep(ms: usize) -> isize {
+    #[cfg(feature="debug_exit")]
+    trace!("kernel:pid[{}] sys_sleep", current_task().unwrap().pid.0);
+    if let Some(result) = seccomp::enforce(SYSCALL_SLEEP) {
+        return result;
+    }
+    if ms == 0 {
+        suspend_current_and_run_next();
+        return 0;
+    }
+    let task = current_task().unwrap();
+    let deadline = deadline_after(ms * 1000);
+    TIMEOUT_QUEUE.register(deadline, task.clone(), Arc::new(SleepTimer) as Arc<dyn TimeoutWaitable>);
+    block_current_and_run_next();
+    TIMEOUT_QUEUE.take_timed_out(&task);
+    0
+}
+
+/// Block the calling task for the `(sec, nsec)` duration at `req`,
+/// POSIX-`nanosleep`-style, writing any unslept remainder to `rem` (if
+/// non-null). Shares [`SYSCALL_SLEEP`]'s filter slot with [`sys_sleep`]
+/// above -- 101 is real `nanosleep`'s syscall number, and `sys_sleep`
+/// already claimed it for this kernel's coarser ms-resolution sleep, so
+/// there's only one slot to enforce against for either entry point.
+///
+/// `rem` is always written as zero and this always returns `0`: the
+/// early-wake/EINTR path only matters if something can interrupt a
+/// blocked task before its deadline, and nothing in this tree can --
+/// `sys_kill`'s note above (before `sys_yield`) explains why there's no
+/// pid-indexed registry to deliver a signal through in the first place.
+/// [`TIMEOUT_QUEUE`] is the only thing that ever wakes a [`SleepTimer`]
+/// wait, so every `sys_nanosleep` call runs to completion.
+pub fn sys_nanosleep(req: *const TimeSpec, rem: *mut TimeSpec) -> isize {
+    #[cfg(feature="debug_exit")]
+    trace!(
+        "kernel:pid[{}] sys_nanosleep",
+        current_task().unwrap().pid.0
+    );
+    if let Some(result) = seccomp::enforce(SYSCALL_SLEEP) {
+        return result;
+    }
+
+    let token = current_user_token();
+    let duration = read_object::<TimeSpec>(token, req as usize);
+    let us = duration.sec * 1_000_000 + duration.nsec / 1_000;
+    if us == 0 {
+        suspend_current_and_run_next();
+    } else {
+        let task = current_task().unwrap();
+        let deadline = deadline_after(us as usize);
+        TIMEOUT_QUEUE.register(deadline, task.clone(), Arc::new(SleepTimer) as Arc<dyn TimeoutWaitable>);
+        block_current_and_run_next();
+        TIMEOUT_QUEUE.take_timed_out(&task);
+    }
+
+    if !rem.is_null() {
+        write_object(token, rem as usize, &TimeSpec { sec: 0, nsec: 0 });
+    }
+    0
+}
+
+/// `sys_getppid` can't land from this file: it would read a parent
+/// `Weak<TaskControlBlock>` off the current task, but `TaskControlBlock`
+/// (in `crate::task::task`) declares no such field -- only
+/// `TaskControlBlockInner::children`, the child-side half of the
+/// relationship, made it into this tree. Re-parenting orphans to
+/// initproc on exit is `exit_current_and_run_next`'s job once that field
+/// exists, and it isn't in this tree either. Even `sys_getpid` just below
+/// is already relying on a `pid` field `TaskControlBlock` doesn't
+/// declare; whoever owns that struct needs to add both.
 ///
 pub fn sys_getpid() -> isize {
     #[cfg(feature="debug_exit")]
     trace!("kernel: sys_getpid pid:{}", current_task().unwrap().pid.0);
+    if let Some(result) = seccomp::enforce(SYSCALL_GETPID) {
+        return result;
+    }
+    check_traced();
     current_task().unwrap().pid.0 as isize
 }
 
+/// `sys_vfork() -> isize` -- fork a child that borrows the parent's
+/// address space outright instead of copying (or COW-sharing) it, with
+/// the parent suspended until the child `exec`s or exits, can't land
+/// from this file: [`sys_fork`] right below gets its copy of the
+/// parent's memory entirely from `TaskControlBlock::fork()`, which isn't
+/// defined anywhere in this tree (only ever called, as it is a few lines
+/// down) -- there's no address-space-cloning logic here to swap for an
+/// address-space-*sharing* one. Suspending the parent until the child
+/// reaches `exec`/`exit` is a second, separate gap: `sys_waitpid` already
+/// lets a parent collect a child's exit via `children`/`exit_code` (it
+/// polls, returning `-2` while the child still runs), but nothing resumes a blocked parent *early* on the child merely
+/// `exec`ing rather than exiting -- that needs a flag on the parent
+/// (conventionally `vfork_pending: Option<Arc<TaskControlBlock>>` on
+/// `TaskControlBlockInner`) that `sys_exec`'s success path checks and
+/// wakes, and there's no such field here to check.
 ///
+/// The intended shape, for whoever adds both: `TaskControlBlock::fork()`
+/// grows a `share_memory: bool` parameter (or a sibling
+/// `vfork()` method) that skips the copy and clones the `Arc<MemorySet>`
+/// (presumably behind its own lock already, for COW's sake) directly;
+/// `sys_vfork` sets the new `vfork_pending` flag on itself and blocks via
+/// `block_current_and_run_next()` the same way `sys_sleep` parks; `exec`
+/// and `exit_current_and_run_next` each check their own task for a
+/// parent waiting on `vfork_pending` and `wakeup_task` it before
+/// continuing.
 pub fn sys_fork() -> isize {
     #[cfg(feature="debug_exit")]
     trace!("kernel:pid[{}] sys_fork", current_task().unwrap().pid.0);
+    if let Some(result) = seccomp::enforce(SYSCALL_FORK) {
+        return result;
+    }
+    check_traced();
     let current_task = current_task().unwrap();
     let new_task = current_task.fork();
     let new_pid = new_task.pid.0;
@@ -70,15 +368,106 @@ pub fn sys_fork() -> isize {
     // we do not have to move to next instruction since we have done it before
     // for child process, fork returns 0
     trap_cx.x[10] = 0;
+    // a sandboxed parent must not produce unsandboxed children
+    if let Some(filter) = current_task.filter_snapshot() {
+        new_task.install_filter(filter);
+    }
+    // start the child at the parent's current stride so it doesn't cut to
+    // the front of the stride ordering and starve everyone else
+    new_task.inner_exclusive_access().stride = current_task.inner_exclusive_access().stride;
     // add new task to scheduler
     add_task(new_task);
     new_pid as isize
 }
 
+/// `sys_waittid(tid) -> i32` -- block until thread `tid` exits and
+/// return its exit code, can't land from this file either. The pieces
+/// `sys_waitpid` below reaps a *process* child through (`children`, a
+/// pid-scoped `Vec` on this same `TaskControlBlockInner`) don't cover a
+/// same-process thread: `sys_clone`'s `CLONE_THREAD` path attaches the
+/// new task to `self.process.upgrade()` instead of this task's
+/// `children`, so there's no list here a thread's tid would show up in.
+/// Finding a sibling thread by tid needs whatever the process side keeps
+/// its threads in (a `ProcessControlBlockInner::tasks: Vec<Weak<..>>`
+/// indexed by tid, conventionally), and that type isn't in this tree.
 ///
+/// The shape, for whoever adds it: a joinable thread stays in that list
+/// past exit (status `Exited`, exit code already recorded the way
+/// `sys_exit` records it here) until `sys_waittid` removes it; blocking
+/// would reuse `block_current_and_run_next`/`wakeup_task` the same way
+/// `sys_waitpid` would if it blocked instead of polling. `-1` for a tid
+/// never seen, `-2` for one that's running but not yet `Exited`.
+///
+/// Freeing a thread's tid and kernel stack promptly at exit (for a
+/// `sys_detach`'d thread) or at join (for a joinable one) needs the same
+/// missing piece as `sys_waittid` above -- the per-process task list to
+/// mark `detached` on and the `TID_ALLOCATOR`/`KSTACK_ALLOCATOR` bitmaps
+/// to free back into, conventionally owned by `task/manager.rs` and
+/// `task/id.rs` respectively. Neither exists in this tree, and process
+/// teardown sweeping any thread still running at that point would need
+/// the same list again. There's nothing here to add a `detached` bit to.
+
+/// Share the parent's open file table instead of copying it. This kernel
+/// keeps the file table on the process alongside `memory_set`, so this
+/// flag alone (without `CLONE_VM`/`CLONE_THREAD`) can't be honored
+/// independently; see [`crate::task::TaskControlBlock::clone_task`].
+pub const CLONE_FILES: usize = 0x0400;
+/// Install `tls` into the new task's thread-pointer register
+pub const CLONE_SETTLS: usize = 0x80000;
+
+/// Create a new task, attaching it to the same process as the caller
+/// (and so sharing its `memory_set`/`fd_table`) when `flags` requests
+/// `CLONE_VM`/`CLONE_THREAD`-style sharing, instead of always
+/// deep-copying into a new process the way `sys_fork` does. `stack`, if
+/// non-zero, becomes the child's user stack pointer so several
+/// `CLONE_VM` tasks can run as threads in one address space.
+pub fn sys_clone(flags: usize, stack: usize, _ptid: usize, tls: usize, _ctid: usize) -> isize {
+    #[cfg(feature="debug_exit")]
+    trace!("kernel:pid[{}] sys_clone flags={:#x}", current_task().unwrap().pid.0, flags);
+    if let Some(result) = seccomp::enforce(SYSCALL_CLONE) {
+        return result;
+    }
+    check_traced();
+    let current_task = current_task().unwrap();
+    let new_task = current_task.clone_task(flags);
+    let new_pid = new_task.pid.0;
+    // modify trap context of new_task, because it returns immediately after switching
+    let trap_cx = new_task.inner_exclusive_access().get_trap_cx();
+    // for child process, clone returns 0
+    trap_cx.x[10] = 0;
+    if stack != 0 {
+        trap_cx.x[2] = stack;
+    }
+    if flags & CLONE_SETTLS != 0 {
+        trap_cx.x[4] = tls;
+    }
+    if let Some(filter) = current_task.filter_snapshot() {
+        new_task.install_filter(filter);
+    }
+    new_task.inner_exclusive_access().stride = current_task.inner_exclusive_access().stride;
+    add_task(new_task);
+    new_pid as isize
+}
+
+/// Extending this to `sys_exec(path, argv: *const *const u8) -> isize`
+/// can't land from this file: translating the NULL-terminated `argv`
+/// array into owned strings is straightforward with
+/// [`crate::mm::translated_str`] (the same helper used for `path`
+/// below), but laying them out on the new task's user stack in the
+/// conventional `argc`/`argv`/strings order has to happen after
+/// `task.exec()` has already swapped in the new address space and
+/// placed the initial stack pointer -- and `exec()` itself (and the
+/// `TrapContext` fields `a0`/`a1` would be written into) live on
+/// `TaskControlBlock`, not defined in this tree, only called here.
+/// There's no stack pointer or `memory_set` on this end to copy the
+/// strings into.
 pub fn sys_exec(path: *const u8) -> isize {
     #[cfg(feature="debug_exit")]
     trace!("kernel:pid[{}] sys_exec", current_task().unwrap().pid.0);
+    if let Some(result) = seccomp::enforce(SYSCALL_EXEC) {
+        return result;
+    }
+    check_traced();
     let token = current_user_token();
     let path = translated_str(token, path);
     if let Some(app_inode) = open_file(path.as_str(), OpenFlags::RDONLY) {
@@ -93,9 +482,17 @@ pub fn sys_exec(path: *const u8) -> isize {
 
 /// If there is not a child process whose pid is same as given, return -1.
 /// Else if there is a child process but it is still running, return -2.
+/// A traced child that's stopped (`TaskStatus::Traced`) also reports its
+/// pid, the same way a real `waitpid` returns on `WIFSTOPPED`, without
+/// reaping it -- the tracer is expected to inspect/resume it via
+/// `sys_ptrace` rather than having it removed from the children list.
 pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
     #[cfg(feature="debug_exit")]
     trace!("kernel::pid[{}] sys_waitpid [{}]", current_task().unwrap().pid.0, pid);
+    if let Some(result) = seccomp::enforce(SYSCALL_WAITPID) {
+        return result;
+    }
+    check_traced();
     let task = current_task().unwrap();
     // find a child process
 
@@ -122,17 +519,27 @@ pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
         // ++++ temporarily access child PCB exclusively
         let exit_code = child.inner_exclusive_access().exit_code;
         // ++++ release child PCB
-        *translated_refmut(inner.memory_set.token(), exit_code_ptr) = exit_code;
+        write_object(inner.memory_set.token(), exit_code_ptr as usize, &exit_code);
         found_pid as isize
+    } else if let Some(traced_child) = inner.children.iter().find(|p| {
+        // ++++ temporarily access child PCB exclusively
+        p.inner_exclusive_access().task_status == TaskStatus::Traced
+            && (pid == -1 || pid as usize == p.getpid())
+        // ++++ release child PCB
+    }) {
+        traced_child.getpid() as isize
     } else {
         -2
     }
     // ---- release current PCB automatically
 }
 
-/// YOUR JOB: get time with second and microsecond
-/// HINT: You might reimplement it with virtual memory management.
-/// HINT: What if [`TimeVal`] is splitted by two pages ?
+/// Report the time since boot as a `(seconds, microseconds)` pair,
+/// POSIX-`gettimeofday`-style; there is no RTC behind it (see
+/// [`sys_clock_gettime`]). `_tz` is accepted but ignored, the
+/// same as most kernels' timezone-less `gettimeofday`. Writes through
+/// [`write_object`] rather than a raw pointer dereference so a `TimeVal`
+/// that happens to straddle a page boundary is still written correctly.
 pub fn sys_get_time(_ts: *mut TimeVal, _tz: usize) -> isize {
     #[cfg(feature="debug_exit")]
     trace!(
@@ -140,99 +547,375 @@ pub fn sys_get_time(_ts: *mut TimeVal, _tz: usize) -> isize {
         current_task().unwrap().pid.0
     );
 
-    let curtsk = current_task().unwrap();
-    let task_inner = curtsk.inner_exclusive_access();
-    let virt_ts = VirtAddr::from(_ts as usize);
-    let pge_ts = task_inner.memory_set.translate(virt_ts.floor()).unwrap();
-    let ts = PhysAddr::from(usize::from(PhysAddr::from(pge_ts.ppn())) + virt_ts.page_offset()).get_mut::<TimeVal>();
-
     let us = get_time_us();
-    *ts = TimeVal {
+    let time_val = TimeVal {
         sec: us / 1_000_000,
         usec: us % 1_000_000,
     };
+    write_object(current_user_token(), _ts as usize, &time_val);
     0
 }
 
-/// YOUR JOB: Finish sys_task_info to pass testcases
-/// HINT: You might reimplement it with virtual memory management.
-/// HINT: What if [`TaskInfo`] is splitted by two pages ?
-pub fn sys_task_info(_ti: *mut TaskInfo) -> isize {
+/// Report the time of clock `clk_id` as a [`TimeSpec`]. `clk_id` must be
+/// [`CLOCK_REALTIME`] or [`CLOCK_MONOTONIC`] -- this kernel has no RTC
+/// and no notion of wall-clock vs. uptime-since-boot beyond what
+/// [`get_time_ns`] already reports, so both clocks read the same
+/// monotonically-increasing nanosecond counter. Writes through
+/// [`write_object`] so a page-split `TimeSpec` is still written
+/// correctly, the same as [`sys_get_time`].
+pub fn sys_clock_gettime(clk_id: usize, ts: *mut TimeSpec) -> isize {
     #[cfg(feature="debug_exit")]
     trace!(
-        "kernel:pid[{}] sys_task_info",
+        "kernel:pid[{}] sys_clock_gettime",
         current_task().unwrap().pid.0
     );
 
-    let curtsk = current_task().unwrap();
-    let task_inner = curtsk.inner_exclusive_access();
-    let virt_ts = VirtAddr::from(_ti as usize);
-    let pge_ts = task_inner.memory_set.translate(virt_ts.floor()).unwrap();
-    let ti = PhysAddr::from(usize::from(PhysAddr::from(pge_ts.ppn())) + virt_ts.page_offset()).get_mut::<TaskInfo>();
-    drop(task_inner);
+    if let Some(result) = seccomp::enforce(SYSCALL_CLOCK_GETTIME) {
+        return result;
+    }
+    if clk_id != CLOCK_REALTIME && clk_id != CLOCK_MONOTONIC {
+        return -1;
+    }
 
-    *ti = curtsk.get_taskinfo();
+    let ns = get_time_ns();
+    let time_spec = TimeSpec {
+        sec: ns / 1_000_000_000,
+        nsec: ns % 1_000_000_000,
+    };
+    write_object(current_user_token(), ts as usize, &time_spec);
     0
 }
 
-/// YOUR JOB: Implement mmap.
-pub fn sys_mmap(_start: usize, _len: usize, _port: usize) -> isize {
+/// Report the calling task's accumulated CPU time as a [`Times`],
+/// POSIX-`times(2)`-style. Reads straight off `TaskControlBlock::statis`
+/// rather than going through [`TaskControlBlock::inner_exclusive_access`]
+/// the way [`sys_task_info`] does, since `statis` (unlike
+/// `TaskControlBlockInner`) isn't behind a lock.
+pub fn sys_times(buf: *mut Times) -> isize {
     #[cfg(feature="debug_exit")]
     trace!(
-        "kernel:pid[{}] sys_mmap",
+        "kernel:pid[{}] sys_times",
         current_task().unwrap().pid.0
     );
+    if let Some(result) = seccomp::enforce(SYSCALL_TIMES) {
+        return result;
+    }
 
     let curtsk = current_task().unwrap();
-    let mut task_inner = curtsk.inner_exclusive_access();
+    let times = Times {
+        utime: curtsk.statis.cpu_time_us,
+        stime: 0,
+    };
+    write_object(current_user_token(), buf as usize, &times);
+    0
+}
+
+/// Fill `buf` with `len` pseudo-random bytes from [`crate::sync::rng`],
+/// the `getrandom` syscall. `flags` is accepted but unused -- there's no
+/// `/dev/urandom`-vs-`/dev/random` distinction to make here, since
+/// `rng::fill_bytes` never blocks waiting on entropy. Goes through
+/// [`translated_byte_buffer`] rather than [`write_object`] since `len` is
+/// arbitrary and the destination can straddle any number of pages.
+pub fn sys_getrandom(buf: *mut u8, len: usize, _flags: usize) -> isize {
+    #[cfg(feature="debug_exit")]
+    trace!(
+        "kernel:pid[{}] sys_getrandom: len={}",
+        current_task().unwrap().pid.0,
+        len,
+    );
+    if let Some(result) = seccomp::enforce(SYSCALL_GETRANDOM) {
+        return result;
+    }
 
-    let virt_start = VirtAddr::from(_start);
+    let token = current_user_token();
+    let mut data = alloc::vec![0u8; len];
+    crate::sync::rng::fill_bytes(&mut data);
+    let mut iter = UserBuffer::new(translated_byte_buffer(token, buf, len)).into_iter();
+    for byte in &data {
+        if let Some(ptr) = iter.next() {
+            unsafe {
+                *ptr = *byte;
+            }
+        } else {
+            break;
+        }
+    }
+    len as isize
+}
 
-    if !virt_start.aligned() {
-        warn!("kernel: mmap start is not aligned!");
+/// Set the calling task's CPU-time budget to `ms` milliseconds, or clear
+/// it (unlimited) if `ms == 0`. Stores into
+/// `TaskControlBlockInner::cpu_limit_us` for [`TaskStatis::over_budget`]
+/// to check against `statis.cpu_time_us` -- see that method's doc
+/// comment for why nothing actually polls it yet in this tree, so
+/// setting a budget here doesn't terminate anything by itself until a
+/// timer-interrupt watchdog exists to call it.
+pub fn sys_set_cpu_limit(ms: usize) -> isize {
+    #[cfg(feature="debug_exit")]
+    trace!(
+        "kernel:pid[{}] sys_set_cpu_limit: ms={}",
+        current_task().unwrap().pid.0,
+        ms,
+    );
+    if let Some(result) = seccomp::enforce(SYSCALL_SET_CPU_LIMIT) {
+        return result;
+    }
+
+    let curtsk = current_task().unwrap();
+    curtsk.inner_exclusive_access().cpu_limit_us = ms.saturating_mul(1000);
+    0
+}
+
+/// Set `pid`'s (`0` meaning the caller) advisory CPU affinity mask to
+/// `mask`, stored in `TaskControlBlockInner::cpu_affinity`. Rejects a
+/// zero mask -- it would claim the task fits on no CPU at all, which
+/// can't ever be honored. `pid` is resolved the same way `sys_ptrace`
+/// resolves a tracee: the caller itself, or one of its not-yet-reaped
+/// children via [`find_child`] -- there's no pid-indexed registry in
+/// this tree (see the `sys_kill` note above) to reach an arbitrary
+/// unrelated pid through.
+pub fn sys_sched_setaffinity(pid: usize, mask: usize) -> isize {
+    #[cfg(feature="debug_exit")]
+    trace!(
+        "kernel:pid[{}] sys_sched_setaffinity: pid={} mask={:#x}",
+        current_task().unwrap().pid.0, pid, mask,
+    );
+    if let Some(result) = seccomp::enforce(SYSCALL_SCHED_SETAFFINITY) {
+        return result;
+    }
+    if mask == 0 {
         return -1;
     }
+    let curtsk = current_task().unwrap();
+    let target = if pid == 0 || pid == curtsk.getpid() {
+        curtsk
+    } else {
+        match find_child(pid) {
+            Some(child) => child,
+            None => return -1,
+        }
+    };
+    target.inner_exclusive_access().cpu_affinity = mask;
+    0
+}
+
+/// Report `pid`'s CPU affinity mask as set by `sys_sched_setaffinity`,
+/// resolved the same way. A task that has never called
+/// `sys_sched_setaffinity` reports `usize::MAX` (every CPU), matching
+/// the "no restriction set yet" default a real multicore kernel would
+/// report.
+pub fn sys_sched_getaffinity(pid: usize) -> isize {
+    #[cfg(feature="debug_exit")]
+    trace!(
+        "kernel:pid[{}] sys_sched_getaffinity: pid={}",
+        current_task().unwrap().pid.0, pid,
+    );
+    if let Some(result) = seccomp::enforce(SYSCALL_SCHED_GETAFFINITY) {
+        return result;
+    }
+    let curtsk = current_task().unwrap();
+    let target = if pid == 0 || pid == curtsk.getpid() {
+        curtsk
+    } else {
+        match find_child(pid) {
+            Some(child) => child,
+            None => return -1,
+        }
+    };
+    target.inner_exclusive_access().cpu_affinity as isize
+}
+
+/// Report the calling task's status, per-syscall invocation counts, and
+/// running time. Like [`sys_get_time`], writes through [`write_object`]
+/// so a page-split `TaskInfo` is still written correctly.
+pub fn sys_task_info(_ti: *mut TaskInfo) -> isize {
+    #[cfg(feature="debug_exit")]
+    trace!(
+        "kernel:pid[{}] sys_task_info",
+        current_task().unwrap().pid.0
+    );
+
+    let curtsk = current_task().unwrap();
+    let info = curtsk.get_taskinfo();
+    write_object(current_user_token(), _ti as usize, &info);
+    0
+}
+
+/// `fd` value meaning "anonymous mapping, no backing file" for [`sys_mmap`]
+pub const MMAP_ANONYMOUS_FD: isize = -1;
+/// Writes to a `MAP_SHARED` mapping are written back to the backing file
+/// by `munmap`
+pub const MAP_SHARED: usize = 0x01;
+/// Writes to a `MAP_PRIVATE` mapping are never written back
+pub const MAP_PRIVATE: usize = 0x02;
+/// Honor `start` exactly instead of treating it as a placement hint
+pub const MAP_FIXED: usize = 0x10;
+
+/// Virtual address hint-mode `mmap` (`start == 0`, no `MAP_FIXED`) starts
+/// scanning from when picking a free range
+const MMAP_HINT_BASE: usize = 0x6000_0000;
+
+/// Whether every page in `[pgn_start, pgn_end)` is currently unmapped
+fn mmap_range_is_free(memory_set: &MemorySet, pgn_start: VirtPageNum, pgn_end: VirtPageNum) -> bool {
+    !(usize::from(pgn_start)..usize::from(pgn_end))
+        .into_iter()
+        .map(|pg| memory_set.translate(VirtPageNum::from(pg)))
+        .any(|x| x.map_or(false, |pte| pte.is_valid()))
+}
+
+/// Find `pages` consecutive free virtual pages at or above
+/// [`MMAP_HINT_BASE`], for hint-mode `mmap`
+fn find_free_area(memory_set: &MemorySet, pages: usize) -> Option<VirtAddr> {
+    let mut base_page = MMAP_HINT_BASE / PAGE_SIZE;
+    while base_page.checked_add(pages).is_some() {
+        let start = VirtPageNum::from(base_page);
+        let end = VirtPageNum::from(base_page + pages);
+        if mmap_range_is_free(memory_set, start, end) {
+            return Some(VirtAddr::from(base_page * PAGE_SIZE));
+        }
+        base_page += pages;
+    }
+    None
+}
+
+/// Map `len` bytes into the caller's address space with permissions
+/// `port`. `start == 0` (without `MAP_FIXED`) asks the kernel to pick the
+/// range itself; otherwise `start` must be page-aligned and unmapped.
+/// `fd`/`offset` back the mapping with an open file's contents instead of
+/// a zero-filled anonymous region when `fd != MMAP_ANONYMOUS_FD`; `flags`
+/// selects `MAP_SHARED` (written back to the file on `munmap`) vs
+/// `MAP_PRIVATE`.
+///
+/// Turning `insert_framed_area` below lazy -- PTEs left invalid at mmap
+/// time, frames allocated on first touch in the page-fault handler -- is
+/// out of reach from this file. `insert_framed_area`/`MapPermission` are
+/// only ever imported here (via the `mm::{*}` glob above), never
+/// defined: the actual allocation strategy lives on `MemorySet`'s side in
+/// `crate::mm`, which isn't in this tree, and neither is the page-fault
+/// trap handler that a lazy mapping would need to allocate from on
+/// demand. There's no frame-count bookkeeping reachable here either, to
+/// verify a "touched 3 of 100 pages" test against.
+///
+/// A guard page below the user stack is a different gap again, and one
+/// this file has even less reach into: the user stack's range is laid
+/// out wherever a task's `MemorySet` is first built (`MemorySet::new_bin`
+/// or equivalent, in `crate::mm`/`crate::loader` -- neither in this
+/// tree), not anywhere `sys_mmap`/`sys_munmap` touch, and distinguishing
+/// "fault in the guard region" from an ordinary bad access is the page-
+/// fault trap handler's job, which also isn't in this tree. There's no
+/// stack-setup code anywhere in this snapshot to add the reservation to.
+pub fn sys_mmap(start: usize, len: usize, port: usize, flags: usize, fd: isize, offset: usize) -> isize {
+    #[cfg(feature="debug_exit")]
+    trace!(
+        "kernel:pid[{}] sys_mmap",
+        current_task().unwrap().pid.0
+    );
+    if let Some(result) = seccomp::enforce(SYSCALL_MMAP) {
+        return result;
+    }
+    check_traced();
 
-    if ((_port & 0x07) == 0) || ((_port & !0x07) != 0) {
+    if ((port & 0x07) == 0) || ((port & !0x07) != 0) {
         warn!("kernel: mmap invalid port attr!");
         return -1;
     }
 
-    let virt_end = VirtAddr::from(_start + _len);
+    if len == 0 {
+        warn!("kernel: mmap zero length!");
+        return -1;
+    }
+    if start.checked_add(len).is_none() {
+        warn!("kernel: mmap range overflows!");
+        return -1;
+    }
+
+    if fd != MMAP_ANONYMOUS_FD && (flags & (MAP_SHARED | MAP_PRIVATE)).count_ones() != 1 {
+        warn!("kernel: mmap needs exactly one of MAP_SHARED/MAP_PRIVATE for a file-backed mapping!");
+        return -1;
+    }
+
+    let curtsk = current_task().unwrap();
+    let mut task_inner = curtsk.inner_exclusive_access();
+
+    let pages = (len + PAGE_SIZE - 1) / PAGE_SIZE;
+    let virt_start = if start == 0 && (flags & MAP_FIXED) == 0 {
+        match find_free_area(&task_inner.memory_set, pages) {
+            Some(va) => va,
+            None => {
+                warn!("kernel: mmap found no free range for hint!");
+                return -1;
+            }
+        }
+    } else {
+        let virt_start = VirtAddr::from(start);
+        if !virt_start.aligned() {
+            warn!("kernel: mmap start is not aligned!");
+            return -1;
+        }
+        virt_start
+    };
+
+    let virt_end = VirtAddr::from(usize::from(virt_start) + len);
     let pgn_start = virt_start.floor();
     let pgn_end = virt_end.ceil();
     #[cfg(feature="debug_exit")]
     trace!("Checking map: [{:?}, {:?})", pgn_start, pgn_end);
-    if (usize::from(pgn_start)..usize::from(pgn_end))
-                .into_iter()
-                .map(|pg|task_inner.memory_set.translate(VirtPageNum::from(pg)))
-                .any(|x|if let Some(pte) = x { if pte.is_valid() { trace!("Found mmapped {:?}", pte); true} else {false}  } else {false}) {
-            warn!("kernel: mmap part of range mapped!");
-        return -1;                    
+    if !mmap_range_is_free(&task_inner.memory_set, pgn_start, pgn_end) {
+        warn!("kernel: mmap part of range mapped!");
+        return -1;
     }
 
-    task_inner.memory_set.insert_framed_area(virt_start, virt_end, MapPermission::from(_port));
-    0
+    task_inner.memory_set.insert_framed_area(virt_start, virt_end, MapPermission::from(port));
+
+    if fd != MMAP_ANONYMOUS_FD {
+        let fd = fd as usize;
+        let file = match task_inner.fd_table.get(fd) {
+            Some(Some(file)) if file.readable() => file.clone(),
+            _ => {
+                warn!("kernel: mmap invalid fd!");
+                return -1;
+            }
+        };
+        let token = task_inner.memory_set.token();
+        task_inner.mmap_regions.push(MmapRegion {
+            start: virt_start,
+            len,
+            file: file.clone(),
+            offset,
+            shared: (flags & MAP_SHARED) != 0,
+        });
+        drop(task_inner);
+        // SEEK_SET
+        file.lseek(offset as isize, 0);
+        file.read(UserBuffer::new(translated_byte_buffer(token, usize::from(virt_start) as *const u8, len)));
+    }
+
+    usize::from(virt_start) as isize
 }
 
-/// YOUR JOB: Implement munmap.
-pub fn sys_munmap(_start: usize, _len: usize) -> isize {
+/// Unmap `[start, start + len)`. A `MAP_SHARED` file-backed mapping has
+/// its current contents written back to the backing file first.
+pub fn sys_munmap(start: usize, len: usize) -> isize {
     #[cfg(feature="debug_exit")]
     trace!(
         "kernel:pid[{}] sys_munmap",
         current_task().unwrap().pid.0
     );
+    if let Some(result) = seccomp::enforce(SYSCALL_MUNMAP) {
+        return result;
+    }
+    check_traced();
     let curtsk = current_task().unwrap();
     let mut task_inner = curtsk.inner_exclusive_access();
 
-    let virt_start = VirtAddr::from(_start);
+    let virt_start = VirtAddr::from(start);
 
     if !virt_start.aligned() {
         warn!("kernel: mmap start is not aligned!");
         return -1;
     }
 
-    let virt_end = VirtAddr::from(_start + _len);
+    let virt_end = VirtAddr::from(start + len);
     let pgn_start = virt_start.floor();
     let pgn_end = virt_end.ceil();
     #[cfg(feature="debug_exit")]
@@ -241,17 +924,160 @@ pub fn sys_munmap(_start: usize, _len: usize) -> isize {
             .into_iter().map(|pg|task_inner.memory_set.translate(VirtPageNum::from(pg)))
             .all(|x|if let Some(pte) = x { if pte.is_valid() { trace!("Found mmapped {:?}", pte); true} else {false}  } else {false}) {
         warn!("kernel: munmap part of range not mapped!");
-        return -1;                    
+        return -1;
     }
 
+    // A munmap range doesn't have to line up with a whole mapping: it can
+    // fall in the middle of one, trim off its front or back, or span
+    // several. Walk every region that overlaps at all and write back (if
+    // `MAP_SHARED`) and re-track exactly the part that's left live.
+    let unmap_start = start;
+    let unmap_end = start + len;
+    let token = task_inner.memory_set.token();
+    let mut survivors = Vec::new();
+    for region in task_inner.mmap_regions.drain(..) {
+        let region_start = usize::from(region.start);
+        let region_end = region_start + region.len;
+        if region_end <= unmap_start || region_start >= unmap_end {
+            // no overlap with the range being unmapped
+            survivors.push(region);
+            continue;
+        }
+        let overlap_start = region_start.max(unmap_start);
+        let overlap_end = region_end.min(unmap_end);
+        if region.shared {
+            let overlap_offset = region.offset + (overlap_start - region_start);
+            region.file.lseek(overlap_offset as isize, 0);
+            region.file.write(UserBuffer::new(translated_byte_buffer(
+                token,
+                overlap_start as *const u8,
+                overlap_end - overlap_start,
+            )));
+        }
+        if overlap_start > region_start {
+            // front remainder survives, unchanged
+            survivors.push(MmapRegion {
+                start: region.start,
+                len: overlap_start - region_start,
+                file: region.file.clone(),
+                offset: region.offset,
+                shared: region.shared,
+            });
+        }
+        if overlap_end < region_end {
+            // back remainder survives, re-based past the unmapped part
+            survivors.push(MmapRegion {
+                start: VirtAddr::from(overlap_end),
+                len: region_end - overlap_end,
+                file: region.file.clone(),
+                offset: region.offset + (overlap_end - region_start),
+                shared: region.shared,
+            });
+        }
+    }
+    task_inner.mmap_regions = survivors;
+
     task_inner.memory_set.remove_area_with_start_vpn(pgn_start);
     0
 }
 
+/// Flush `[addr, addr + len)`'s dirty contents back to the backing
+/// inode without unmapping it, POSIX `msync(2)`-style. File-backed
+/// mapping already happens through [`sys_mmap`]'s `fd`/`offset`
+/// parameters (no separate `sys_mmap_file` needed, the same way `munmap`
+/// above already writes a `MAP_SHARED` region back on unmap); `msync`
+/// just exposes that same writeback without requiring the caller to tear
+/// the mapping down first. A no-op for `MAP_PRIVATE` regions, and for any
+/// part of the range that isn't a file-backed mapping at all.
+pub fn sys_msync(addr: usize, len: usize) -> isize {
+    #[cfg(feature="debug_exit")]
+    trace!(
+        "kernel:pid[{}] sys_msync",
+        current_task().unwrap().pid.0
+    );
+    if let Some(result) = seccomp::enforce(SYSCALL_MSYNC) {
+        return result;
+    }
+    check_traced();
+
+    let virt_start = VirtAddr::from(addr);
+    if !virt_start.aligned() {
+        warn!("kernel: msync addr is not aligned!");
+        return -1;
+    }
+
+    let curtsk = current_task().unwrap();
+    let task_inner = curtsk.inner_exclusive_access();
+    let token = task_inner.memory_set.token();
+    let sync_start = addr;
+    let sync_end = addr + len;
+    for region in task_inner.mmap_regions.iter() {
+        if !region.shared {
+            continue;
+        }
+        let region_start = usize::from(region.start);
+        let region_end = region_start + region.len;
+        if region_end <= sync_start || region_start >= sync_end {
+            continue;
+        }
+        let overlap_start = region_start.max(sync_start);
+        let overlap_end = region_end.min(sync_end);
+        let overlap_offset = region.offset + (overlap_start - region_start);
+        region.file.lseek(overlap_offset as isize, 0);
+        region.file.write(UserBuffer::new(translated_byte_buffer(
+            token,
+            overlap_start as *const u8,
+            overlap_end - overlap_start,
+        )));
+    }
+    0
+}
+
+/// `sys_shmget`/`sys_shmat`/`sys_shmdt` -- explicit shared memory backed
+/// by refcounted frames in a global segment table -- can't land from
+/// this file either, and for much the same reason as `resident_pages`
+/// below: mapping a segment's exact frames into a caller's address space
+/// needs `MemorySet`/`PageTable`/frame-allocator APIs from `crate::mm`,
+/// which isn't in this tree, only imported. A global segment table keyed
+/// by `key`/`shmid` would also need somewhere process-independent to
+/// live (a `lazy_static` in whatever module owns frame allocation,
+/// conventionally), which this file has no access to either.
+///
+/// `MemorySet::resident_pages(&self) -> usize` and the `sys_proc_mem(pid)`
+/// syscall that would report it can't land from this file: `MemorySet`
+/// lives in `crate::mm`, which isn't in this tree, only imported (see the
+/// `mm::{*}` glob above) and used through `task_inner.memory_set` the way
+/// [`sys_mmap`]/[`sys_munmap`] do. Summing map-area frame counts instead
+/// of re-walking the page table means iterating whatever field holds
+/// `MemorySet`'s areas, and that field isn't visible here either --
+/// there's no method to add `resident_pages` to on this end.
+///
+/// `sys_brk(new_brk)` -- reporting/setting the break as an absolute
+/// address rather than a relative delta -- can't land from this file
+/// either: `sys_sbrk` below already calls
+/// `current_task().unwrap().change_program_brk(size)`, but that method
+/// isn't defined anywhere in this tree (`TaskControlBlock`'s `impl` block
+/// in `crate::task::task` has no such method), only called here. There's
+/// no current-break value reachable from this file to read back for the
+/// `new_brk == 0` query case, let alone set absolutely; whoever adds
+/// `change_program_brk` needs to add an absolute-address sibling next to
+/// it.
+///
+/// Whether that same missing `change_program_brk` actually frees frames
+/// on a shrink, and hands back zeroed pages on a later re-grow, is a
+/// question only its own implementation can answer -- there's nothing in
+/// this file to audit or fix, since the method (and the heap area it
+/// would shrink/grow on `task_inner.memory_set`) isn't defined anywhere
+/// in this tree.
+///
 /// change data segment size
 pub fn sys_sbrk(size: i32) -> isize {
     #[cfg(feature="debug_exit")]
     trace!("kernel:pid[{}] sys_sbrk", current_task().unwrap().pid.0);
+    if let Some(result) = seccomp::enforce(SYSCALL_SBRK) {
+        return result;
+    }
+    check_traced();
     if let Some(old_brk) = current_task().unwrap().change_program_brk(size) {
         old_brk as isize
     } else {
@@ -267,11 +1093,24 @@ pub fn sys_spawn(_path: *const u8) -> isize {
         "kernel:pid[{}] sys_spawn",
         current_task().unwrap().pid.0
     );
+    if let Some(result) = seccomp::enforce(SYSCALL_SPAWN) {
+        return result;
+    }
+    check_traced();
 
     let token = current_user_token();
     let path = translated_str(token, _path);
     if let Some(app_inode) = open_file(path.as_str(), OpenFlags::RDONLY) {
         let all_data = app_inode.read_all();
+        // Reject a malformed ELF before `spawn()` allocates a pid and
+        // frames for it: `spawn()`/`exec()` don't return a `Result` (and
+        // aren't defined in this tree to make them), so there is no
+        // rollback path once a new task exists -- catching the bad magic
+        // here is the only leak-free way to fail this request.
+        if all_data.len() < 4 || &all_data[0..4] != b"\x7fELF" {
+            warn!("kernel: spawn failed: '{}' is not a valid ELF", path);
+            return -1;
+        }
         let current_task = current_task().unwrap();
         let new_task = current_task.spawn();
         let new_pid = new_task.pid.0;
@@ -280,6 +1119,11 @@ pub fn sys_spawn(_path: *const u8) -> isize {
         // we do not have to move to next instruction since we have done it before
         // for child process, fork returns 0
         trap_cx.x[10] = 0;
+        // a sandboxed parent must not produce unsandboxed children
+        if let Some(filter) = current_task.filter_snapshot() {
+            new_task.install_filter(filter);
+        }
+        new_task.inner_exclusive_access().stride = current_task.inner_exclusive_access().stride;
 
         //let task = current_task().unwrap();
         new_task.exec(all_data.as_slice());
@@ -295,24 +1139,179 @@ pub fn sys_spawn(_path: *const u8) -> isize {
     }
 }
 
-// YOUR JOB: Set task priority.
+/// Upper bound [`sys_set_priority`] clamps `_prio` to. A `_prio` above
+/// this would make `pass = BIG_STRIDE / _prio` round down to `0`, which
+/// would let this task win every stride comparison forever and starve
+/// everything else; clamping keeps `pass` bounded away from that.
+pub const PRIO_MAX: isize = 10_000;
+
+/// Set the current task's scheduling priority (and, optionally, its
+/// policy). `policy` is `0` for the regular `SchedPolicy::Stride` class
+/// and `1` for `SchedPolicy::Fifo`.
+///
+/// Priority only ever changes a task's `pass` (`BIG_STRIDE / priority`),
+/// the increment added to its `stride` each time it runs; `stride` itself
+/// is left alone so the task keeps its place in the stride ordering
+/// instead of jumping back to the front.
 ///
-pub fn sys_set_priority(_prio: isize) -> isize {
+/// `_prio` is clamped to `[2, PRIO_MAX]` (returning the clamped value,
+/// not `-1`, for anything above the max -- only a `_prio` below the
+/// floor is rejected outright) and the resulting `pass` is floored at
+/// `1`, so stride always advances even for a maximally-deprioritized
+/// task. Passing `_prio == -1` queries the current priority (derived
+/// back out of `pass`) without changing anything.
+pub fn sys_set_priority(_prio: isize, _policy: usize) -> isize {
     #[cfg(feature="debug_exit")]
     trace!(
         "kernel:pid[{}] sys_set_priority",
         current_task().unwrap().pid.0
     );
+    if let Some(result) = seccomp::enforce(SYSCALL_SET_PRIORITY) {
+        return result;
+    }
+    check_traced();
+
+    let curtsk = current_task().unwrap();
+    let mut task_inner = curtsk.inner_exclusive_access();
+
+    if _prio == -1 {
+        return (BIG_STRIDE / task_inner.pass.max(1)) as isize;
+    }
 
     if _prio < 2 {
         warn!("kernel:pid[{}] set_priority failed: Invalid priority", _prio);
         return -1;
     }
+    let clamped = _prio.min(PRIO_MAX);
 
-    let curtsk = current_task().unwrap();
-    let mut task_inner = curtsk.inner_exclusive_access();
+    let policy = match _policy {
+        1 => SchedPolicy::Fifo,
+        _ => SchedPolicy::Stride,
+    };
+
+    task_inner.policy = policy;
+    task_inner.pass = (BIG_STRIDE / clamped as usize).max(1);
+    clamped
+}
+
+/// Ask to be traced by the parent: syscall entry will stop this task and
+/// hand control to the parent's `waitpid` instead of running the handler
+pub const PTRACE_TRACEME: usize = 0;
+/// Read a word from the tracee's address space at `addr`
+pub const PTRACE_PEEKDATA: usize = 2;
+/// Write the word `data` into the tracee's address space at `addr`
+pub const PTRACE_POKEDATA: usize = 5;
+/// Resume a stopped tracee
+pub const PTRACE_CONT: usize = 7;
+/// Resume a stopped tracee. Handled the same as [`PTRACE_CONT`]: the
+/// tracee runs on until its next syscall entry, not for a single
+/// instruction
+pub const PTRACE_SINGLESTEP: usize = 9;
+/// Copy the tracee's trap context (saved registers) into `data`
+pub const PTRACE_GETREGS: usize = 12;
+/// Overwrite the tracee's trap context from `data`
+pub const PTRACE_SETREGS: usize = 13;
+/// Start tracing an already-running child
+pub const PTRACE_ATTACH: usize = 16;
 
-    //task_inner.stride = 0;
-    task_inner.pass = _prio as usize / BIG_STRIDE;
-    _prio
+/// Stop the current task here if it's been marked for tracing by
+/// `PTRACE_TRACEME`/`PTRACE_ATTACH`, standing in for the trap-level
+/// syscall-entry check a real ptrace implementation would do before
+/// ever reaching a handler. Flips the task to `TaskStatus::Traced` and
+/// blocks it *without* putting it back on the ready queue --
+/// `suspend_current_and_run_next` would mark it `Ready` and re-enqueue it
+/// immediately, defeating the stop. `PTRACE_CONT`/`PTRACE_SINGLESTEP` are
+/// what explicitly put it back on the ready queue. `traced` stays set
+/// across the stop, the same way a real tracee keeps trapping at every
+/// subsequent syscall until it's detached.
+pub fn check_traced() {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    if inner.traced {
+        inner.task_status = TaskStatus::Traced;
+        drop(inner);
+        block_current_and_run_next();
+    }
+}
+
+/// Find a not-yet-reaped child of the current task by pid, the same
+/// lookup `sys_waitpid` does
+fn find_child(pid: usize) -> Option<Arc<TaskControlBlock>> {
+    current_task()
+        .unwrap()
+        .inner_exclusive_access()
+        .children
+        .iter()
+        .find(|p| p.getpid() == pid)
+        .cloned()
+}
+
+/// A minimal ptrace: TRACEME/ATTACH stop the tracee at its next syscall
+/// entry, PEEKDATA/POKEDATA inspect its address space through the
+/// page-crossing-safe accessor, GETREGS/SETREGS expose its trap context,
+/// and CONT/SINGLESTEP resume it.
+pub fn sys_ptrace(request: usize, pid: usize, addr: usize, data: usize) -> isize {
+    #[cfg(feature="debug_exit")]
+    trace!(
+        "kernel:pid[{}] sys_ptrace request={} pid={}",
+        current_task().unwrap().pid.0, request, pid
+    );
+
+    match request {
+        PTRACE_TRACEME => {
+            current_task().unwrap().inner_exclusive_access().traced = true;
+            0
+        }
+        PTRACE_ATTACH => match find_child(pid) {
+            Some(child) => {
+                child.inner_exclusive_access().traced = true;
+                0
+            }
+            None => -1,
+        },
+        PTRACE_PEEKDATA => match find_child(pid) {
+            Some(child) => {
+                let token = child.inner_exclusive_access().memory_set.token();
+                read_object::<usize>(token, addr) as isize
+            }
+            None => -1,
+        },
+        PTRACE_POKEDATA => match find_child(pid) {
+            Some(child) => {
+                let token = child.inner_exclusive_access().memory_set.token();
+                write_object(token, addr, &data);
+                0
+            }
+            None => -1,
+        },
+        PTRACE_GETREGS => match find_child(pid) {
+            Some(child) => {
+                let trap_cx = *child.inner_exclusive_access().get_trap_cx();
+                write_object(current_user_token(), data, &trap_cx);
+                0
+            }
+            None => -1,
+        },
+        PTRACE_SETREGS => match find_child(pid) {
+            Some(child) => {
+                let trap_cx = read_object(current_user_token(), data);
+                *child.inner_exclusive_access().get_trap_cx() = trap_cx;
+                0
+            }
+            None => -1,
+        },
+        PTRACE_CONT | PTRACE_SINGLESTEP => match find_child(pid) {
+            Some(child) => {
+                let mut inner = child.inner_exclusive_access();
+                if inner.task_status == TaskStatus::Traced {
+                    inner.task_status = TaskStatus::Ready;
+                    drop(inner);
+                    add_task(child);
+                }
+                0
+            }
+            None => -1,
+        },
+        _ => -1,
+    }
 }
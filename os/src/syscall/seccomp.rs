@@ -0,0 +1,69 @@
+//! Seccomp-style per-task syscall filtering
+use crate::syscall::process::read_object;
+use crate::task::{current_task, exit_current_and_run_next, SeccompAction, SyscallFilter};
+
+/// Consult the calling task's installed filter for `syscall_id`, the way
+/// every syscall handler in this kernel is expected to before doing any
+/// work. Returns `Some(result)` if the handler should be skipped entirely
+/// and `result` returned to userspace instead (a denied syscall's errno);
+/// on `Kill` the task is exited here and this never returns. `None`
+/// means the filter allows the syscall through.
+pub fn enforce(syscall_id: usize) -> Option<isize> {
+    match current_task().unwrap().syscall_action(syscall_id) {
+        SeccompAction::Allow => None,
+        SeccompAction::Deny(errno) => Some(errno as isize),
+        SeccompAction::Kill => {
+            exit_current_and_run_next(-1);
+            unreachable!("exit_current_and_run_next never returns");
+        }
+    }
+}
+
+/// One override entry passed to [`sys_seccomp`]: the syscall to match and
+/// the action to take for it.
+///
+/// `action` encodes an [`SeccompAction`]: `0` is Allow, `-1` is Kill, any
+/// other (necessarily negative) value is Deny with that errno.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct SeccompRule {
+    pub syscall_id: u32,
+    pub action: i32,
+}
+
+fn decode_action(code: i32) -> SeccompAction {
+    match code {
+        0 => SeccompAction::Allow,
+        -1 => SeccompAction::Kill,
+        errno => SeccompAction::Deny(errno),
+    }
+}
+
+/// Install (or further restrict) the calling task's seccomp filter.
+///
+/// `mode` is the default action (encoded the same way as [`SeccompRule::action`])
+/// applied to any syscall not named in `filter_ptr`; `filter_ptr`/`len`
+/// describe an array of `len` [`SeccompRule`]s giving per-syscall overrides.
+/// Filters are irrevocable and inherited across `fork`: see
+/// [`crate::task::TaskControlBlock::install_filter`].
+pub fn sys_seccomp(mode: i32, filter_ptr: *const SeccompRule, len: usize) -> isize {
+    #[cfg(feature = "debug_exit")]
+    trace!(
+        "kernel:pid[{}] sys_seccomp: mode={} len={}",
+        current_task().unwrap().pid.0,
+        mode,
+        len,
+    );
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    let token = inner.memory_set.token();
+    let mut filter = SyscallFilter::new(decode_action(mode));
+    for i in 0..len {
+        let vaddr = filter_ptr as usize + i * core::mem::size_of::<SeccompRule>();
+        let rule = read_object::<SeccompRule>(token, vaddr);
+        filter.set(rule.syscall_id as usize, decode_action(rule.action));
+    }
+    drop(inner);
+    task.install_filter(filter);
+    0
+}
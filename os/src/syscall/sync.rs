@@ -0,0 +1,182 @@
+//! Synchronization-primitive-related syscalls
+//!
+//! `condvar_list` below needs to exist on `ProcessControlBlockInner`,
+//! parallel to `mutex_list`/`semaphore_list`. That struct lives outside
+//! this tree -- `os/src/sync/mutex.rs` and `semaphore.rs`, which already
+//! depend on `mutex_list`/`semaphore_list`/`resmon`/`current_process()`
+//! being real, predate this chunk (they're part of the baseline), so
+//! there's no file here defining `ProcessControlBlockInner` to add a
+//! field to without guessing at and conflicting with its real
+//! declaration. Whoever owns that file needs to add `condvar_list: Vec<Option<Arc<Condvar>>>`.
+use alloc::sync::Arc;
+
+use crate::sync::{futex, Condvar, Mutex};
+use crate::syscall::process::{read_object, translated_phys_addr};
+use crate::syscall::seccomp;
+use crate::task::{current_process, current_task, current_user_token};
+
+/// Syscall numbers for the handlers in this file, as consulted by
+/// [`seccomp::enforce`] before each one runs
+const SYSCALL_CONDVAR_CREATE: usize = 1030;
+const SYSCALL_CONDVAR_SIGNAL: usize = 1031;
+const SYSCALL_CONDVAR_BROADCAST: usize = 1033;
+const SYSCALL_CONDVAR_WAIT: usize = 1032;
+const SYSCALL_ENABLE_DEADLOCK_DETECT: usize = 1034;
+const SYSCALL_FUTEX: usize = 98;
+
+/// `op` values [`sys_futex`] accepts. Real `futex(2)` also has
+/// `FUTEX_PRIVATE_FLAG` and several other ops (`FUTEX_CMP_REQUEUE`,
+/// `FUTEX_WAKE_OP`, ...); only the pair worth supporting without a
+/// process-shared vs. private distinction (this kernel has no notion of
+/// shared anonymous mappings to distinguish) are implemented.
+pub const FUTEX_WAIT: usize = 0;
+/// See [`FUTEX_WAIT`]
+pub const FUTEX_WAKE: usize = 1;
+
+/// Toggle deadlock detection for the calling process. Every
+/// synchronization primitive's `SyncRes::check()` gates on
+/// `ProcessControlBlock::detect_deadlock`, so this one switch controls
+/// whether mutexes, semaphores, condvars, and rwlocks actually run the
+/// detector or just let a task block and hope.
+pub fn sys_enable_deadlock_detect(enabled: usize) -> isize {
+    #[cfg(feature = "debug_sem")]
+    trace!(
+        "kernel:pid[{}] sys_enable_deadlock_detect: enabled={}",
+        current_task().unwrap().process.upgrade().unwrap().getpid(),
+        enabled,
+    );
+    if let Some(result) = seccomp::enforce(SYSCALL_ENABLE_DEADLOCK_DETECT) {
+        return result;
+    }
+    current_process().set_deadlock_detect(enabled != 0);
+    0
+}
+
+/// condvar create syscall
+///
+/// # Return
+/// * Return the ID of the condvar created.
+pub fn sys_condvar_create() -> isize {
+    #[cfg(feature = "debug_sem")]
+    trace!(
+        "kernel:pid[{}] sys_condvar_create",
+        current_task().unwrap().process.upgrade().unwrap().getpid()
+    );
+    if let Some(result) = seccomp::enforce(SYSCALL_CONDVAR_CREATE) {
+        return result;
+    }
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    let id = if let Some(id) = process_inner
+        .condvar_list
+        .iter()
+        .enumerate()
+        .find(|(_, item)| item.is_none())
+        .map(|(id, _)| id)
+    {
+        process_inner.condvar_list[id] = Some(Arc::new(Condvar::new()));
+        id
+    } else {
+        process_inner
+            .condvar_list
+            .push(Some(Arc::new(Condvar::new())));
+        process_inner.condvar_list.len() - 1
+    };
+    id as isize
+}
+
+/// condvar signal syscall
+pub fn sys_condvar_signal(condvar_id: usize) -> isize {
+    #[cfg(feature = "debug_sem")]
+    trace!(
+        "kernel:pid[{}] sys_condvar_signal",
+        current_task().unwrap().process.upgrade().unwrap().getpid()
+    );
+    if let Some(result) = seccomp::enforce(SYSCALL_CONDVAR_SIGNAL) {
+        return result;
+    }
+    let process = current_process();
+    let process_inner = process.inner_exclusive_access();
+    let condvar = Arc::clone(process_inner.condvar_list[condvar_id].as_ref().unwrap());
+    drop(process_inner);
+    condvar.signal();
+    0
+}
+
+/// condvar broadcast syscall
+pub fn sys_condvar_broadcast(condvar_id: usize) -> isize {
+    #[cfg(feature = "debug_sem")]
+    trace!(
+        "kernel:pid[{}] sys_condvar_broadcast",
+        current_task().unwrap().process.upgrade().unwrap().getpid()
+    );
+    if let Some(result) = seccomp::enforce(SYSCALL_CONDVAR_BROADCAST) {
+        return result;
+    }
+    let process = current_process();
+    let process_inner = process.inner_exclusive_access();
+    let condvar = Arc::clone(process_inner.condvar_list[condvar_id].as_ref().unwrap());
+    drop(process_inner);
+    condvar.broadcast();
+    0
+}
+
+/// condvar wait syscall
+///
+/// `mutex_id` must refer to a mutex the caller currently holds; it is
+/// released for the duration of the wait and re-acquired before returning.
+pub fn sys_condvar_wait(condvar_id: usize, mutex_id: usize) -> isize {
+    #[cfg(feature = "debug_sem")]
+    trace!(
+        "kernel:pid[{}] sys_condvar_wait",
+        current_task().unwrap().process.upgrade().unwrap().getpid()
+    );
+    if let Some(result) = seccomp::enforce(SYSCALL_CONDVAR_WAIT) {
+        return result;
+    }
+    let process = current_process();
+    let process_inner = process.inner_exclusive_access();
+    let condvar = Arc::clone(process_inner.condvar_list[condvar_id].as_ref().unwrap());
+    let mutex: Arc<dyn Mutex> = Arc::clone(process_inner.mutex_list[mutex_id].as_ref().unwrap());
+    drop(process_inner);
+    condvar.wait(mutex) as isize
+}
+
+/// `futex(uaddr, op, val)` -- block on or wake waiters of the `u32` word
+/// at `uaddr`, keyed by its physical address so two tasks that reach the
+/// same word through different mappings (or the same mapping at
+/// different times) still rendezvous. `FUTEX_WAIT` re-reads `*uaddr`
+/// after translating it and returns `-1` immediately if it no longer
+/// equals `val`, the same race-check real `futex(2)` makes, then parks
+/// on [`futex::wait`]; `FUTEX_WAKE` wakes up to `val` waiters via
+/// [`futex::wake`] and returns how many actually woke.
+///
+/// Unlike [`Mutex`]/[`Condvar`] above, there's no `_list` handle to
+/// allocate up front -- the futex word itself, wherever it lives in the
+/// caller's address space, is the only identity this needs.
+pub fn sys_futex(uaddr: *mut u32, op: usize, val: u32) -> isize {
+    #[cfg(feature = "debug_sem")]
+    trace!(
+        "kernel:pid[{}] sys_futex: op={} val={}",
+        current_task().unwrap().process.upgrade().unwrap().getpid(),
+        op,
+        val,
+    );
+    if let Some(result) = seccomp::enforce(SYSCALL_FUTEX) {
+        return result;
+    }
+    let token = current_user_token();
+    let key = translated_phys_addr(token, uaddr as usize);
+    match op {
+        FUTEX_WAIT => {
+            let current = read_object::<u32>(token, uaddr as usize);
+            if current != val {
+                return -1;
+            }
+            futex::wait(key);
+            0
+        }
+        FUTEX_WAKE => futex::wake(key, val as usize) as isize,
+        _ => -1,
+    }
+}
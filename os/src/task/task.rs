@@ -1,15 +1,57 @@
 //! Types related to task management
+//!
+//! Copy-on-write fork needs two things neither of which lives in this
+//! file: `TaskControlBlock::fork` (defined alongside `exec`/`spawn`, not
+//! in this tree) would need to map the child's `memory_set` onto the
+//! same frames read-only instead of eagerly duplicating them, and
+//! `MemorySet`/`PageTable` (in `crate::mm`, also not in this tree) would
+//! need refcounted frames plus a write-fault handler that duplicates a
+//! frame lazily and re-maps it writable. `TaskControlBlockInner` here
+//! has nothing CoW-specific to hold -- the refcount lives on the frame
+//! allocator's side, not the task's -- so there's no field to add on
+//! this end. Whoever owns `fork`/`mm` needs to do the actual work.
 
 use super::TaskContext;
 use crate::config::*;
+use crate::fs::File;
+use crate::mm::{MemorySet, PhysPageNum, VirtAddr};
+use easy_fs::Inode;
+use crate::sync::UPSafeCell;
+use crate::timer::get_time_us;
+use crate::trap::TrapContext;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::RefMut;
+
+/// Default scheduling priority for a newly created task
+const DEFAULT_PRIORITY: usize = 16;
+
+/// Upper bound on how many slots a single task's `fd_table` may grow to
+/// (closed-but-unreclaimed slots included), so a program that leaks fds
+/// can't grow the table without bound and exhaust kernel memory
+pub const FD_MAX: usize = 256;
 
 ///
 #[derive(Copy, Clone)]
 pub struct TaskStatis {
     /// The numbers of syscall called by task
     pub syscall_times: [u32; MAX_SYSCALL_NUM],
-    /// Total running time of task
+    /// Milliseconds since boot at which this task was first scheduled
+    /// (`0` if it never has been); see [`TaskControlBlock::mark_started`]
+    /// and [`TaskControlBlock::elapsed_ms`]
     pub starttime: usize,
+    /// Microseconds this task has actually spent running, accumulated
+    /// across every schedule-in/schedule-out pair by
+    /// [`TaskControlBlock::schedule_in`]/[`TaskControlBlock::schedule_out`].
+    /// Distinct from `starttime`/`elapsed_ms`'s wall-clock view: a task
+    /// that slept through half its life has `cpu_time_us` about half of
+    /// `elapsed_ms() * 1000`, where a busy-spinning one has the two
+    /// nearly equal.
+    pub cpu_time_us: usize,
+    /// Set by [`TaskControlBlock::schedule_in`] to the timestamp the next
+    /// [`TaskControlBlock::schedule_out`] subtracts from to fold the
+    /// just-finished run into `cpu_time_us`. `None` while not running.
+    run_since_us: Option<usize>,
 }
 
 impl Default for TaskStatis {
@@ -17,21 +59,528 @@ impl Default for TaskStatis {
         Self {
             syscall_times: [0u32; MAX_SYSCALL_NUM],
             starttime: 0usize,
+            cpu_time_us: 0usize,
+            run_since_us: None,
+        }
+    }
+}
+
+impl TaskStatis {
+    /// Has `cpu_time_us` reached `limit_us`? `limit_us` comes from
+    /// `TaskControlBlockInner::cpu_limit_us` (`0` meaning unlimited, the
+    /// caller's job to check before calling this); kept as a plain
+    /// parameter rather than a field here since `cpu_limit_us` is
+    /// syscall-settable config and belongs behind `inner`'s lock like
+    /// the task's other mutable config, not next to this struct's
+    /// scheduler-only accounting fields.
+    ///
+    /// A watchdog would call this from the timer interrupt handler on
+    /// every tick and terminate the task via `exit_current_and_run_next`
+    /// once it returns `true` -- but the timer interrupt dispatch isn't
+    /// in this tree (no `trap.rs`/`timer.rs` owning tick delivery), so
+    /// this only has somewhere to be called from once that dispatch
+    /// exists, the same gap [`TaskStatis::record_syscall`] documents for
+    /// its own caller.
+    pub fn over_budget(&self, limit_us: usize) -> bool {
+        limit_us != 0 && self.cpu_time_us >= limit_us
+    }
+
+
+    /// Increment this task's count for `syscall_id`, the single place
+    /// every syscall handler's count should flow through so a new
+    /// syscall can't be forgotten by some handler never bumping it.
+    /// Out-of-range ids are ignored rather than panicking, since an
+    /// unrecognized id shouldn't be able to take the kernel down over a
+    /// statistics counter.
+    ///
+    /// This only has somewhere to be called from once the dispatch
+    /// trampoline (the `syscall()` function matching on syscall id) calls
+    /// it centrally -- that trampoline isn't in this tree, so wiring this
+    /// in is whoever owns it adding one line at the top of the match.
+    pub fn record_syscall(&mut self, syscall_id: usize) {
+        if let Some(count) = self.syscall_times.get_mut(syscall_id) {
+            *count += 1;
+        }
+    }
+}
+
+/// Scheduling priority state that can be donated to by priority inheritance
+struct TaskPriorityInner {
+    /// The priority the task was created/configured with
+    base_priority: usize,
+    /// The priority currently in effect, i.e. `max(base_priority, donations)`
+    priority: usize,
+}
+
+/// The disposition a [`SyscallFilter`] assigns to a syscall
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SeccompAction {
+    /// run the syscall as normal
+    Allow,
+    /// skip the syscall and return this (negative) errno to userspace
+    Deny(i32),
+    /// terminate the task instead of running the syscall
+    Kill,
+}
+
+impl SeccompAction {
+    fn rank(&self) -> u8 {
+        match self {
+            SeccompAction::Allow => 0,
+            SeccompAction::Deny(_) => 1,
+            SeccompAction::Kill => 2,
+        }
+    }
+
+    /// Whether `self` is a stricter disposition than `other`, i.e.
+    /// Allow < Deny < Kill. Used to enforce that a filter can only be
+    /// tightened, never loosened, once installed.
+    pub fn stricter_than(&self, other: SeccompAction) -> bool {
+        self.rank() > other.rank()
+    }
+}
+
+/// A per-task seccomp-style filter: an action per syscall number, with a
+/// fallback for any syscall the filter doesn't mention explicitly
+#[derive(Clone)]
+pub struct SyscallFilter {
+    actions: [Option<SeccompAction>; MAX_SYSCALL_NUM],
+    default_action: SeccompAction,
+}
+
+impl SyscallFilter {
+    /// Create an empty filter that falls back to `default_action` for
+    /// every syscall
+    pub fn new(default_action: SeccompAction) -> Self {
+        Self {
+            actions: [None; MAX_SYSCALL_NUM],
+            default_action,
+        }
+    }
+
+    /// Set the disposition for a specific syscall number
+    pub fn set(&mut self, syscall_id: usize, action: SeccompAction) {
+        if syscall_id < MAX_SYSCALL_NUM {
+            self.actions[syscall_id] = Some(action);
+        }
+    }
+
+    /// The disposition that applies to `syscall_id`
+    pub fn decide(&self, syscall_id: usize) -> SeccompAction {
+        self.actions
+            .get(syscall_id)
+            .copied()
+            .flatten()
+            .unwrap_or(self.default_action)
+    }
+}
+
+/// A file-backed mapping still live in a task's address space, so
+/// `munmap` knows whether (and where) to write its pages back. Owned by
+/// [`TaskControlBlockInner::mmap_regions`].
+pub(crate) struct MmapRegion {
+    /// first virtual address the mapping occupies
+    pub start: VirtAddr,
+    /// length of the mapping, in bytes; together with `start` this is
+    /// what lets `munmap` tell a partial unmap (inside this range) from
+    /// one that covers a different mapping entirely
+    pub len: usize,
+    /// backing file
+    pub file: Arc<dyn File>,
+    /// byte offset into `file` the mapping starts at
+    pub offset: usize,
+    /// whether writes are flushed back to `file` on unmap (`MAP_SHARED`)
+    pub shared: bool,
+}
+
+/// State behind [`TaskControlBlock::inner_exclusive_access`]: everything
+/// about a task that changes over its lifetime and needs exclusive access
+/// to touch safely.
+pub struct TaskControlBlockInner {
+    /// The task status in its lifecycle
+    pub task_status: TaskStatus,
+    /// This task's (or its process's) address space
+    pub memory_set: MemorySet,
+    /// Open file descriptor table
+    pub fd_table: Vec<Option<Arc<dyn File>>>,
+    /// Not-yet-reaped child tasks
+    pub children: Vec<Arc<TaskControlBlock>>,
+    /// Exit code recorded by `sys_exit`, read by a parent's `waitpid`
+    pub exit_code: i32,
+    /// Physical page holding this task's saved trap context
+    pub trap_cx_ppn: PhysPageNum,
+    /// This task's current position in the stride ordering; see
+    /// [`stride_less`]/[`takes_priority_over`]
+    pub stride: usize,
+    /// Whether a tracer has asked to stop this task at its next syscall
+    /// entry (`PTRACE_TRACEME`/`PTRACE_ATTACH`); see `check_traced` in
+    /// `crate::syscall::process`
+    pub traced: bool,
+    /// Stride increment added to `stride` each time this task is
+    /// scheduled, derived from its priority (`BIG_STRIDE / priority`)
+    pub pass: usize,
+    /// Scheduling class this task runs under
+    pub policy: SchedPolicy,
+    /// File-backed mappings currently live in `memory_set`, so `munmap`
+    /// knows whether (and where) to write each one back
+    pub(crate) mmap_regions: Vec<MmapRegion>,
+    /// Current working directory, against which `sys_chdir` resolves a
+    /// relative path in `crate::syscall::fs` and which a relative-path
+    /// open would resolve against too, if `open_file` (in `crate::fs`,
+    /// not in this tree) had somewhere to take a base inode other than
+    /// the filesystem root. `fork`/`spawn` (also not in this tree) would
+    /// need to clone this `Arc` onto the child the same way they copy
+    /// `fd_table`.
+    pub cwd: Arc<Inode>,
+    /// CPU-time budget set by `sys_set_cpu_limit`, in microseconds.
+    /// `0` means unlimited. See [`TaskStatis::over_budget`].
+    pub cpu_limit_us: usize,
+    /// Advisory CPU affinity mask set by `sys_sched_setaffinity`, one bit
+    /// per CPU. Nothing in this single-core tree's scheduler consults it
+    /// yet -- `pick_next` picks across the whole ready queue regardless
+    /// -- but storing and reporting it back lets userspace written
+    /// against a future SMP scheduler compile and run unchanged today.
+    pub cpu_affinity: usize,
+}
+
+impl TaskControlBlockInner {
+    /// The trap context saved/restored across user<->kernel transitions
+    pub fn get_trap_cx(&self) -> &'static mut TrapContext {
+        self.trap_cx_ppn.get_mut()
+    }
+
+    /// Whether this task has exited and is waiting to be reaped
+    pub fn is_zombie(&self) -> bool {
+        self.task_status == TaskStatus::Exited
+    }
+
+    /// Drop every open fd, releasing whatever each `Arc<dyn File>` holds
+    /// rather than letting a zombie sit on them until its parent gets
+    /// around to `waitpid`-ing it. Dropping a pipe write end this way
+    /// already makes blocked readers see EOF next time they poll --
+    /// `PipeRingBuffer::all_write_ends_closed` checks strong-reference
+    /// count, not a separately-signalled flag, so there's no wakeup to
+    /// fire here beyond the `Arc` going away; a reader parked in
+    /// `Pipe::read`'s `suspend_current_and_run_next` loop (see
+    /// `os/src/fs/pipe.rs`) re-checks the condition on its very next
+    /// turn. A regular file's last `Arc<Inode>` reference going away
+    /// similarly needs nothing further from here -- easy-fs writes
+    /// through on every `write_at`, not write-back, so there's no
+    /// buffered data left to flush (and no block cache defined in this
+    /// tree to flush it from regardless; see the `fsck`/readahead notes
+    /// in `easy-fs/src/vfs.rs`).
+    ///
+    /// Whoever owns the exit path (not in this tree; see
+    /// `TaskControlBlock::mark_started`'s note on the missing dispatch
+    /// trampoline) should call this before marking the task `Exited`.
+    pub fn close_fds(&mut self) {
+        #[cfg(feature = "fd_leak_warn")]
+        self.warn_unclosed_writable_fds();
+        for fd in self.fd_table.iter_mut() {
+            *fd = None;
+        }
+    }
+
+    /// Teaching aid: log every still-open, writable fd this task is
+    /// about to have torn down out from under it, with the inode id
+    /// each one was writing to, so a student missing a `close` call
+    /// sees it called out at exit instead of the leak passing silently.
+    /// Read-only fds (e.g. a file only ever read from) aren't reported --
+    /// those never had unflushed writes riding on the missing `close` in
+    /// the first place.
+    #[cfg(feature = "fd_leak_warn")]
+    fn warn_unclosed_writable_fds(&self) {
+        let leaked: Vec<u32> = self
+            .fd_table
+            .iter()
+            .filter_map(|fd| fd.as_ref())
+            .filter(|file| file.writable())
+            .filter_map(|file| file.inode())
+            .map(|inode| inode.node_id())
+            .collect();
+        if !leaked.is_empty() {
+            warn!(
+                "kernel: process exiting with {} unclosed writable fd(s), inode ids={:?}",
+                leaked.len(),
+                leaked,
+            );
+        }
+    }
+
+    /// Find the lowest-numbered free slot in `fd_table`, growing the
+    /// table by one if every existing slot is taken. Returns `None`
+    /// instead of growing past [`FD_MAX`], so a descriptor leak fails
+    /// the next `open`/`pipe`/`dup` cleanly rather than growing the
+    /// table forever.
+    pub fn alloc_fd(&mut self) -> Option<usize> {
+        if let Some(fd) = self.fd_table.iter().position(|fd| fd.is_none()) {
+            Some(fd)
+        } else if self.fd_table.len() < FD_MAX {
+            self.fd_table.push(None);
+            Some(self.fd_table.len() - 1)
+        } else {
+            None
         }
     }
 }
 
 /// The task control block (TCB) of a task.
-#[derive(Copy, Clone)]
 pub struct TaskControlBlock {
-    /// The task status in it's lifecycle
-    pub task_status: TaskStatus,
     /// The task context
     pub task_cx: TaskContext,
 
     /// The task statis
     pub statis: TaskStatis,
 
+    /// Everything about this task that changes over its lifetime; see
+    /// [`TaskControlBlockInner`].
+    inner: UPSafeCell<TaskControlBlockInner>,
+
+    /// Priority state, kept behind interior mutability so that a task
+    /// that is blocked and only reachable via `Arc<TaskControlBlock>`
+    /// (e.g. parked in a mutex's `wait_queue`) can still have its
+    /// effective priority raised by priority inheritance.
+    priority: UPSafeCell<TaskPriorityInner>,
+
+    /// Installed seccomp-style syscall filter, consulted by
+    /// `crate::syscall::seccomp::enforce`, which the syscall handlers call
+    /// before doing any work. `None` means unsandboxed.
+    filter: UPSafeCell<Option<SyscallFilter>>,
+}
+
+impl TaskControlBlock {
+    /// Exclusive access to everything about this task that changes over
+    /// its lifetime; see [`TaskControlBlockInner`].
+    pub fn inner_exclusive_access(&self) -> RefMut<'_, TaskControlBlockInner> {
+        self.inner.exclusive_access()
+    }
+
+    /// Record `statis.starttime` the first time this task is scheduled,
+    /// i.e. the instant `TaskInfo::time` should measure elapsed time
+    /// from. A no-op on every call after the first, so a task that keeps
+    /// getting rescheduled doesn't have its clock reset.
+    ///
+    /// Whoever runs a task next (the scheduler's dispatch path) needs to
+    /// call this on the `UnInit`/`Ready` -> `Running` transition; that
+    /// dispatch path isn't in this tree, so nothing calls this yet.
+    pub fn mark_started(&mut self) {
+        if self.statis.starttime == 0 {
+            self.statis.starttime = get_time_us() / 1000;
+        }
+    }
+
+    /// Elapsed wall-clock time since this task first ran, in
+    /// milliseconds -- `0` if [`TaskControlBlock::mark_started`] has
+    /// never run, `now - starttime` after.
+    pub fn elapsed_ms(&self) -> usize {
+        if self.statis.starttime == 0 {
+            0
+        } else {
+            (get_time_us() / 1000).saturating_sub(self.statis.starttime)
+        }
+    }
+
+    /// Start a new CPU-time accrual interval, called from the same
+    /// `UnInit`/`Ready` -> `Running` dispatch path
+    /// [`TaskControlBlock::mark_started`] documents as missing from this
+    /// tree. Also marks `mark_started`, since every schedule-in is a
+    /// valid place for that one-time bookkeeping to happen too.
+    pub fn schedule_in(&mut self) {
+        self.mark_started();
+        self.statis.run_since_us = Some(get_time_us());
+    }
+
+    /// End the current CPU-time accrual interval, folding it into
+    /// `cpu_time_us`. Called from the `Running` -> `Ready`/`Exited`/etc.
+    /// dispatch path, the same one [`TaskControlBlock::schedule_in`]
+    /// documents as missing. A no-op if `schedule_in` was never called
+    /// for the interval being closed out.
+    pub fn schedule_out(&mut self) {
+        if let Some(since) = self.statis.run_since_us.take() {
+            self.statis.cpu_time_us += get_time_us().saturating_sub(since);
+        }
+    }
+
+    /// The task's current effective priority (its own, or a donated one)
+    pub fn priority(&self) -> usize {
+        self.priority.exclusive_access().priority
+    }
+
+    /// The task's original, undonated priority
+    pub fn base_priority(&self) -> usize {
+        self.priority.exclusive_access().base_priority
+    }
+
+    /// Raise the task's effective priority, e.g. when a higher-priority
+    /// task blocks on a mutex this task owns
+    pub fn set_priority(&self, priority: usize) {
+        self.priority.exclusive_access().priority = priority;
+    }
+
+    /// Unwind a priority donation, restoring the task to its base priority
+    pub fn restore_priority(&self) {
+        let mut inner = self.priority.exclusive_access();
+        inner.priority = inner.base_priority;
+    }
+
+    /// Install (or tighten) this task's syscall filter. Filters are
+    /// irrevocable: a syscall's disposition may only move towards being
+    /// stricter (Allow -> Deny -> Kill), never back towards Allow, so a
+    /// task cannot use a second `sys_seccomp` call to escape its own
+    /// sandbox. A task may self-sandbox with no elevated capability.
+    pub fn install_filter(&self, updates: SyscallFilter) {
+        let mut guard = self.filter.exclusive_access();
+        match guard.as_mut() {
+            None => *guard = Some(updates),
+            Some(existing) => {
+                for syscall_id in 0..MAX_SYSCALL_NUM {
+                    let proposed = updates.decide(syscall_id);
+                    if proposed.stricter_than(existing.decide(syscall_id)) {
+                        existing.set(syscall_id, proposed);
+                    }
+                }
+                if updates.default_action.stricter_than(existing.default_action) {
+                    existing.default_action = updates.default_action;
+                }
+            }
+        }
+    }
+
+    /// Clone this task's filter, e.g. so `fork`/`spawn` can install an
+    /// identical copy on the child and keep a sandboxed parent's
+    /// children sandboxed too
+    pub fn filter_snapshot(&self) -> Option<SyscallFilter> {
+        self.filter.exclusive_access().as_ref().cloned()
+    }
+
+    /// What the installed filter says to do about `syscall_id`; `Allow`
+    /// if no filter has been installed
+    pub fn syscall_action(&self, syscall_id: usize) -> SeccompAction {
+        self.filter
+            .exclusive_access()
+            .as_ref()
+            .map(|f| f.decide(syscall_id))
+            .unwrap_or(SeccompAction::Allow)
+    }
+
+    /// Create a new task for `sys_clone`. `memory_set` and `fd_table`
+    /// both live on the owning [`super::ProcessControlBlock`], shared by
+    /// every thread attached to it, so `CLONE_VM`/`CLONE_THREAD` sharing
+    /// is implemented by attaching the new task to the *same* process
+    /// instead of `fork`'s default of copying into a brand new one.
+    /// `CLONE_FILES` without `CLONE_VM` isn't separable in this kernel
+    /// (there is no per-thread-only file table to share independently of
+    /// the address space), so it's treated the same as plain `fork`.
+    pub fn clone_task(self: &Arc<Self>, flags: usize) -> Arc<Self> {
+        if flags & (CLONE_VM | CLONE_THREAD) != 0 {
+            let process = self.process.upgrade().unwrap();
+            process.new_thread()
+        } else {
+            self.fork()
+        }
+    }
+}
+
+/// Share the caller's address space instead of copy-on-fork, so threads
+/// created with this flag run in one address space
+pub const CLONE_VM: usize = 0x0100;
+/// Mark the new task as a thread of the calling process rather than a
+/// separate child process
+pub const CLONE_THREAD: usize = 0x10000;
+
+/// Scheduling policy a task runs under, mirroring the SCHED_OTHER/
+/// SCHED_FIFO split a POSIX scheduler exposes
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SchedPolicy {
+    /// Regular stride-scheduled task: runs in proportion to its priority,
+    /// interleaved with every other `Stride` task
+    Stride,
+    /// Runs ahead of every `Stride` task for as long as it's runnable, as
+    /// an escape hatch for latency-critical work
+    Fifo,
+}
+
+/// Wrapping-safe ordering for stride scheduling: `stride` wraps around
+/// `usize::MAX`, so a plain `a < b` breaks the instant it overflows.
+/// Comparing the wrapped difference as a signed value keeps working
+/// across the wraparound, as long as no task ever falls behind by more
+/// than `usize::MAX / 2` in one go, which `BIG_STRIDE` is sized to
+/// guarantee.
+pub fn stride_less(a: usize, b: usize) -> bool {
+    (a.wrapping_sub(b) as isize) < 0
+}
+
+/// Whether `candidate` should be scheduled ahead of `incumbent`: a
+/// `Fifo` task always wins over a `Stride` one, and between two tasks of
+/// the same policy the one with the (wrapping-safe) smaller `stride`
+/// wins. This is the comparison the scheduler's "pick the ready task
+/// with the smallest stride" pass is built on.
+pub fn takes_priority_over(
+    candidate_policy: SchedPolicy,
+    candidate_stride: usize,
+    incumbent_policy: SchedPolicy,
+    incumbent_stride: usize,
+) -> bool {
+    match (candidate_policy, incumbent_policy) {
+        (SchedPolicy::Fifo, SchedPolicy::Stride) => true,
+        (SchedPolicy::Stride, SchedPolicy::Fifo) => false,
+        _ => stride_less(candidate_stride, incumbent_stride),
+    }
+}
+
+/// Pick which of several ready tasks the scheduler should run next: the
+/// one that `takes_priority_over` every other candidate. Nothing in this
+/// tree calls it: the task manager's fetch path over its ready queue is
+/// not part of this tree.
+///
+/// This is already the overflow-safe comparison a `BinaryHeap<Reverse<_>>`
+/// keyed by `stride` would need for its `Ord` impl -- `takes_priority_over`
+/// doesn't assume anything about how candidates are stored, it just picks
+/// a winner from whatever iterator it's given. Swapping the ready queue
+/// itself from a scan to a heap (for O(log n) pick instead of this O(n)
+/// `reduce`) is the task manager's call, and the task manager isn't in
+/// this tree to change.
+///
+/// Making the stride *increment* itself wrap (`stride =
+/// stride.wrapping_add(pass)`) can't land from this file, or from any
+/// other file in this tree: nothing here ever performs that increment.
+/// `stride` is only ever set wholesale -- seeded from a parent's stride
+/// on fork/spawn/clone (see `syscall::process`) -- and read back by
+/// `stride_less`/`takes_priority_over` above. The actual `stride +=
+/// pass` (or equivalent) belongs in the per-tick scheduling dispatch
+/// that decides a running task has used up its slice and should be
+/// requeued, which lives in the timer-interrupt trap handler; this tree
+/// has no `trap/` module and no `timer.rs` definition, only references
+/// to `crate::timer::get_time_us`/`get_time_ns`, so there's no dispatch
+/// site here to add the increment -- or its overflow handling -- to.
+/// `stride_less` above is already written to tolerate the wraparound
+/// that increment would eventually cause, and `sys_set_priority`
+/// already computes `pass = BIG_STRIDE / priority` floored to `1`
+/// (`os/src/syscall/process.rs`), so once that increment exists
+/// somewhere, wrapping it is the only change still needed.
+pub(crate) fn pick_next<'a>(
+    ready: impl IntoIterator<Item = &'a Arc<TaskControlBlock>>,
+) -> Option<&'a Arc<TaskControlBlock>> {
+    ready.into_iter().reduce(|incumbent, candidate| {
+        let (candidate_policy, candidate_stride) = {
+            let inner = candidate.inner_exclusive_access();
+            (inner.policy, inner.stride)
+        };
+        let (incumbent_policy, incumbent_stride) = {
+            let inner = incumbent.inner_exclusive_access();
+            (inner.policy, inner.stride)
+        };
+        if takes_priority_over(
+            candidate_policy,
+            candidate_stride,
+            incumbent_policy,
+            incumbent_stride,
+        ) {
+            candidate
+        } else {
+            incumbent
+        }
+    })
 }
 
 /// The status of a task
@@ -45,4 +594,7 @@ pub enum TaskStatus {
     Running,
     /// exited
     Exited,
+    /// stopped at a syscall-entry trap for a tracer to inspect, as
+    /// requested by `PTRACE_TRACEME`/`PTRACE_ATTACH`
+    Traced,
 }